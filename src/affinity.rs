@@ -0,0 +1,50 @@
+//! Pinning the benchmark to a single CPU
+//!
+//! ## Authors
+//!
+//! The Veracruz Development Team.
+//!
+//! ## Copyright
+//!
+//! See the file `LICENSING.markdown` in the Veracruz root directory for licensing
+//! and copyright information.
+
+use std::io;
+
+/// Pin the calling thread to one CPU, so migrations between CPUs don't add
+/// jitter to the measurements
+///
+/// Threads spawned afterwards inherit the affinity, so modes using
+/// --threads all share this CPU. This needs sched_setaffinity, so is only
+/// available on Linux with the pin_cpu feature, otherwise it fails with
+/// Unsupported.
+pub fn pin(cpu: usize) -> io::Result<()> {
+    #[cfg(all(target_os = "linux", feature = "pin_cpu"))]
+    {
+        if cpu >= libc::CPU_SETSIZE as usize {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                format!("CPU {} is out of range", cpu)
+            ));
+        }
+
+        // SAFETY: cpu_set_t is plain data, all zeros is an empty set, and
+        // CPU_SET is only given a CPU we checked is within the set
+        let res = unsafe {
+            let mut set = std::mem::zeroed::<libc::cpu_set_t>();
+            libc::CPU_SET(cpu, &mut set);
+            libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set)
+        };
+        if res != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    #[cfg(not(all(target_os = "linux", feature = "pin_cpu")))]
+    {
+        let _ = cpu;
+        Err(io::Error::new(io::ErrorKind::Unsupported,
+            "--pin-cpu needs Linux and a build with --features pin_cpu"
+        ))
+    }
+}
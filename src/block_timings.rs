@@ -0,0 +1,67 @@
+//! Optional timing of individual block operations
+//!
+//! ## Authors
+//!
+//! The Veracruz Development Team.
+//!
+//! ## Copyright
+//!
+//! See the file `LICENSING.markdown` in the Veracruz root directory for licensing
+//! and copyright information.
+
+use crate::Config;
+use std::{
+    convert::TryFrom,
+    fs::File,
+    io::BufWriter,
+    io::Write,
+    time::Duration,
+    time::Instant,
+};
+
+/// Durations of each block operation, recorded only when --block-csv is
+/// given since timing every block adds its own overhead
+///
+/// Aggregates can hide periodic stalls, such as every Nth write paying for
+/// a flush, which show up clearly when these are plotted.
+pub struct BlockTimings {
+    samples: Option<Vec<(u64, Duration)>>,
+}
+
+impl BlockTimings {
+    pub fn new(config: &Config, count: u64) -> Self {
+        Self {
+            samples: config.block_csv.as_ref()
+                .map(|_| Vec::with_capacity(usize::try_from(count).unwrap())),
+        }
+    }
+
+    /// Run a block operation at the given offset, timing it if enabled
+    pub fn time<T>(&mut self, offset: u64, f: impl FnOnce() -> T) -> T {
+        match &mut self.samples {
+            Some(samples) => {
+                let stopwatch = Instant::now();
+                let res = f();
+                samples.push((offset, stopwatch.elapsed()));
+                res
+            }
+            None => f(),
+        }
+    }
+
+    /// Write the recorded timings as `index,offset,duration_ns`, this
+    /// should only be called after the timed region
+    pub fn write_csv(&self, config: &Config) {
+        let (path, samples) = match (&config.block_csv, &self.samples) {
+            (Some(path), Some(samples)) => (path, samples),
+            _ => return,
+        };
+
+        let mut file = BufWriter::new(File::create(path).unwrap());
+        writeln!(file, "index,offset,duration_ns").unwrap();
+        for (i, (offset, duration)) in samples.iter().enumerate() {
+            writeln!(file, "{},{},{}", i, offset, duration.as_nanos()).unwrap();
+        }
+        file.flush().unwrap();
+    }
+}
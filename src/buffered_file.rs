@@ -9,61 +9,61 @@
 //! See the file `LICENSING.markdown` in the Veracruz root directory for licensing
 //! and copyright information.
 
+use crate::{
+    Config,
+    coverage::Coverage,
+    hint,
+    marker::mark,
+    file::warm_up,
+    report::Report,
+    retry::with_retry,
+    rng::Prng,
+};
 use std::{
     cell::RefCell,
     cmp::min,
     convert::TryFrom,
     fs::File,
-    hint,
     io::Write,
     io::Read,
     io::Seek,
     io::SeekFrom,
     io::BufReader,
     io::BufWriter,
-    iter,
     mem,
     ops::DerefMut,
     time::Duration,
     time::Instant,
 };
 
-/// xorshift64 for providing deterministic pseudo-random numbers
-fn xorshift64(seed: u64) -> impl Iterator<Item=u64> {
-    let mut x = seed;
-    iter::repeat_with(move || {
-        x ^= x << 13;
-        x ^= x >> 7;
-        x ^= x << 17;
-        x
-    })
-}
-
 
 /// Write a large file in-order
-pub fn write_inorder(size: u64, block_size: usize, run: u32) -> Duration {
-    let path = format!("/scratch/buffered_write_inorder_{}_{}_{}.txt", size, block_size, run);
-    let mut file = BufWriter::new(File::create(&path).unwrap());
-    let mut prng = xorshift64(42);
+pub fn write_inorder(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/buffered_write_inorder_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
+    let mut prng = Prng::new(config.prng, seed);
     let mut buffer = vec![0u8; block_size];
 
     let stopwatch = Instant::now();
 
     for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
         for (j, x) in
             (&mut prng)
-                .take(usize::try_from(
-                    min(i+u64::try_from(block_size).unwrap(), size) - i
-                ).unwrap())
+                .take(step_size)
                 .enumerate()
         {
             buffer[j] = x as u8;
         }
+        mark(config, &mut buffer);
 
         
         hint::black_box({
-            let input = hint::black_box(&buffer);
-            file.write_all(input).unwrap();
+            let input = hint::black_box(&buffer[..step_size]);
+            with_retry(retries, || file.write_all(input)).unwrap();
         });
     }
 
@@ -73,58 +73,66 @@ pub fn write_inorder(size: u64, block_size: usize, run: u32) -> Duration {
 
     let duration = stopwatch.elapsed();
 
+    let metadata = file.get_ref().metadata().unwrap();
+
     // Truncate the file! Otherwise Veracruz may try to copy it back over
     // into the user's fs, which is a waste of (significant) time...
     //
     let file = file.into_inner().unwrap();
     file.set_len(0).unwrap();
 
-    duration
+    Report::new(duration)
+        .with_footprint(&metadata)
 }
 
 /// Update a large file in-order
-pub fn update_inorder(size: u64, block_size: usize, run: u32) -> Duration {
-    let path = format!("/scratch/buffered_update_inorder_{}_{}_{}.txt", size, block_size, run);
-    let mut file = BufWriter::new(File::create(&path).unwrap());
-    let mut prng = xorshift64(42);
+pub fn update_inorder(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/buffered_update_inorder_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
+    let mut prng = Prng::new(config.prng, seed);
     let mut buffer = vec![0u8; block_size];
 
     // first create/fill the file
     for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
         for (j, x) in
             (&mut prng)
-                .take(usize::try_from(
-                    min(i+u64::try_from(block_size).unwrap(), size) - i
-                ).unwrap())
+                .take(step_size)
                 .enumerate()
         {
             buffer[j] = x as u8;
         }
+        mark(config, &mut buffer);
 
-        file.write_all(&buffer).unwrap();
+        with_retry(retries, || file.write_all(&buffer[..step_size])).unwrap();
     }
 
     mem::drop(file);
-    let mut file = BufWriter::new(File::create(&path).unwrap());
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
 
     // now measure updates
     let stopwatch = Instant::now();
 
     for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
         for (j, x) in
             (&mut prng)
-                .take(usize::try_from(
-                    min(i+u64::try_from(block_size).unwrap(), size) - i
-                ).unwrap())
+                .take(step_size)
                 .enumerate()
         {
             buffer[j] = x as u8;
         }
+        mark(config, &mut buffer);
 
         
         hint::black_box({
-            let input = hint::black_box(&buffer);
-            file.write_all(input).unwrap();
+            let input = hint::black_box(&buffer[..step_size]);
+            with_retry(retries, || file.write_all(input)).unwrap();
         });
     }
 
@@ -140,33 +148,39 @@ pub fn update_inorder(size: u64, block_size: usize, run: u32) -> Duration {
     let file = file.into_inner().unwrap();
     file.set_len(0).unwrap();
 
-    duration
+    Report::new(duration)
 }
 
 /// Read a large file in-order
-pub fn read_inorder(size: u64, block_size: usize, run: u32) -> Duration {
-    let path = format!("/scratch/buffered_read_inorder_{}_{}_{}.txt", size, block_size, run);
-    let mut file = BufWriter::new(File::create(&path).unwrap());
-    let mut prng = xorshift64(42);
+pub fn read_inorder(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, buf_capacity, .. } = *config;
+    let path = format!("{}/buffered_read_inorder_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
+    let mut prng = Prng::new(config.prng, seed);
     let mut buffer = vec![0u8; block_size];
 
     // first create/fill the file
     for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
         for (j, x) in
             (&mut prng)
-                .take(usize::try_from(
-                    min(i+u64::try_from(block_size).unwrap(), size) - i
-                ).unwrap())
+                .take(step_size)
                 .enumerate()
         {
             buffer[j] = x as u8;
         }
+        mark(config, &mut buffer);
 
-        file.write_all(&buffer).unwrap();
+        with_retry(retries, || file.write_all(&buffer[..step_size])).unwrap();
     }
 
     mem::drop(file);
-    let mut file = BufReader::new(File::open(&path).unwrap());
+    let mut file = BufReader::with_capacity(
+        buf_capacity,
+        with_retry(retries, || File::open(&path)).unwrap()
+    );
 
     // Now measure reads
     let stopwatch = Instant::now();
@@ -177,7 +191,7 @@ pub fn read_inorder(size: u64, block_size: usize, run: u32) -> Duration {
         ).unwrap();
         
         hint::black_box({
-            file.read_exact(hint::black_box(&mut buffer[..step_size])).unwrap();
+            with_retry(retries, || file.read_exact(hint::black_box(&mut buffer[..step_size]))).unwrap();
             &buffer
         });
     }
@@ -185,47 +199,138 @@ pub fn read_inorder(size: u64, block_size: usize, run: u32) -> Duration {
     let duration = stopwatch.elapsed();
 
     mem::drop(file);
-    let file = File::create(&path).unwrap();
+    let file = with_retry(retries, || File::create(&path)).unwrap();
+
+    // Truncate the file! Otherwise Veracruz may try to copy it back over
+    // into the user's fs, which is a waste of (significant) time...
+    //
+    file.set_len(0).unwrap();
+
+    Report::new(duration)
+        .with("buf_capacity", buf_capacity)
+}
+
+/// Read the same large file in-order, first straight from the File and
+/// then through a BufReader of --buf-capacity bytes
+///
+/// Both passes run in one process on one file, so the speedup from
+/// buffering isn't skewed by variance between separate runs. The file is
+/// read once, untimed, before either pass, so the raw pass going first
+/// doesn't warm caches for the buffered pass alone.
+pub fn read_buffered_compare(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, buf_capacity, .. } = *config;
+    let path = format!("{}/read_buffered_compare_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
+    let mut prng = Prng::new(config.prng, seed);
+    let mut buffer = vec![0u8; block_size];
+
+    // first create/fill the file
+    for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
+        for (j, x) in
+            (&mut prng)
+                .take(step_size)
+                .enumerate()
+        {
+            buffer[j] = x as u8;
+        }
+        mark(config, &mut buffer);
+
+        with_retry(retries, || file.write_all(&buffer[..step_size])).unwrap();
+    }
+
+    mem::drop(file);
+
+    // read everything once first, so both passes are warm
+    let mut file = with_retry(retries, || File::open(&path)).unwrap();
+    warm_up(&mut file, &mut buffer, retries);
+    mem::drop(file);
+
+    // Now measure raw reads
+    let mut file = with_retry(retries, || File::open(&path)).unwrap();
+    let raw_duration = measure_read(config, &mut file, &mut buffer);
+    mem::drop(file);
+
+    // and buffered reads
+    let mut file = BufReader::with_capacity(
+        buf_capacity,
+        with_retry(retries, || File::open(&path)).unwrap()
+    );
+    let duration = measure_read(config, &mut file, &mut buffer);
+    mem::drop(file);
+
+    let file = with_retry(retries, || File::create(&path)).unwrap();
 
     // Truncate the file! Otherwise Veracruz may try to copy it back over
     // into the user's fs, which is a waste of (significant) time...
     //
     file.set_len(0).unwrap();
 
-    duration
+    Report::new(duration)
+        .with("buf_capacity", buf_capacity)
+        .with("raw_runtime", raw_duration.as_secs_f64())
+        .with("buffered_speedup",
+            raw_duration.as_secs_f64() / duration.as_secs_f64()
+        )
+}
+
+/// Read a whole file in-order in block_size reads, returning how long that
+/// took
+fn measure_read(config: &Config, file: &mut impl Read, buffer: &mut [u8]) -> Duration {
+    let Config { size, block_size, retries, .. } = *config;
+    let stopwatch = Instant::now();
+
+    for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
+
+        hint::black_box({
+            with_retry(retries, || file.read_exact(hint::black_box(&mut buffer[..step_size]))).unwrap();
+            &buffer
+        });
+    }
+
+    stopwatch.elapsed()
 }
 
 /// Write a large file in reverse-order
-pub fn write_reversed(size: u64, block_size: usize, run: u32) -> Duration {
-    let path = format!("/scratch/buffered_write_reversed_{}_{}_{}.txt", size, block_size, run);
-    let mut file = BufWriter::new(File::create(&path).unwrap());
-    let mut prng = xorshift64(42);
+pub fn write_reversed(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/buffered_write_reversed_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
+    let mut prng = Prng::new(config.prng, seed);
     let mut buffer = vec![0u8; block_size];
 
     let stopwatch = Instant::now();
 
-    // this division is a workaround for Range<u64> limitations
+    // this division is a workaround for Range<u64> limitations, rounded
+    // up so a trailing partial block is included
     for i in
-        (0..size/u64::try_from(block_size).unwrap())
+        (0..size.div_ceil(u64::try_from(block_size).unwrap()))
             .rev()
             .map(|i| i*u64::try_from(block_size).unwrap())
     {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
         for (j, x) in
             (&mut prng)
-                .take(usize::try_from(
-                    min(i+u64::try_from(block_size).unwrap(), size) - i
-                ).unwrap())
+                .take(step_size)
                 .enumerate()
         {
             buffer[j] = x as u8;
         }
+        mark(config, &mut buffer);
 
         
         hint::black_box({
             file.seek(SeekFrom::Start(i)).unwrap();
 
-            let input = hint::black_box(&buffer);
-            file.write_all(input).unwrap();
+            let input = hint::black_box(&buffer[..step_size]);
+            with_retry(retries, || file.write_all(input)).unwrap();
         });
     }
 
@@ -235,65 +340,74 @@ pub fn write_reversed(size: u64, block_size: usize, run: u32) -> Duration {
 
     let duration = stopwatch.elapsed();
 
+    let metadata = file.get_ref().metadata().unwrap();
+
     // Truncate the file! Otherwise Veracruz may try to copy it back over
     // into the user's fs, which is a waste of (significant) time...
     //
     let file = file.into_inner().unwrap();
     file.set_len(0).unwrap();
 
-    duration
+    Report::new(duration)
+        .with_footprint(&metadata)
 }
 
 /// Update a large file in reverse-order
-pub fn update_reversed(size: u64, block_size: usize, run: u32) -> Duration {
-    let path = format!("/scratch/buffered_update_reversed_{}_{}_{}.txt", size, block_size, run);
-    let mut file = BufWriter::new(File::create(&path).unwrap());
-    let mut prng = xorshift64(42);
+pub fn update_reversed(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/buffered_update_reversed_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
+    let mut prng = Prng::new(config.prng, seed);
     let mut buffer = vec![0u8; block_size];
 
     // first create/fill the file
     for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
         for (j, x) in
             (&mut prng)
-                .take(usize::try_from(
-                    min(i+u64::try_from(block_size).unwrap(), size) - i
-                ).unwrap())
+                .take(step_size)
                 .enumerate()
         {
             buffer[j] = x as u8;
         }
+        mark(config, &mut buffer);
 
-        file.write_all(&buffer).unwrap();
+        with_retry(retries, || file.write_all(&buffer[..step_size])).unwrap();
     }
 
     mem::drop(file);
-    let mut file = BufWriter::new(File::create(&path).unwrap());
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
 
     // now measure updates
     let stopwatch = Instant::now();
 
-    // this division is a workaround for Range<u64> limitations
+    // this division is a workaround for Range<u64> limitations, rounded
+    // up so a trailing partial block is included
     for i in
-        (0..size/u64::try_from(block_size).unwrap())
+        (0..size.div_ceil(u64::try_from(block_size).unwrap()))
             .rev()
             .map(|i| i*u64::try_from(block_size).unwrap())
     {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
         for (j, x) in
             (&mut prng)
-                .take(usize::try_from(
-                    min(i+u64::try_from(block_size).unwrap(), size) - i
-                ).unwrap())
+                .take(step_size)
                 .enumerate()
         {
             buffer[j] = x as u8;
         }
+        mark(config, &mut buffer);
 
         
         hint::black_box({
             file.seek(SeekFrom::Start(i)).unwrap();
 
-            let input = hint::black_box(&buffer);
-            file.write_all(input).unwrap();
+            let input = hint::black_box(&buffer[..step_size]);
+            with_retry(retries, || file.write_all(input)).unwrap();
         });
     }
 
@@ -309,40 +423,47 @@ pub fn update_reversed(size: u64, block_size: usize, run: u32) -> Duration {
     let file = file.into_inner().unwrap();
     file.set_len(0).unwrap();
 
-    duration
+    Report::new(duration)
 }
 
 /// Read a large file in reverse-order
-pub fn read_reversed(size: u64, block_size: usize, run: u32) -> Duration {
-    let path = format!("/scratch/buffered_read_reversed_{}_{}_{}.txt", size, block_size, run);
-    let mut file = BufWriter::new(File::create(&path).unwrap());
-    let mut prng = xorshift64(42);
+pub fn read_reversed(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, buf_capacity, .. } = *config;
+    let path = format!("{}/buffered_read_reversed_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
+    let mut prng = Prng::new(config.prng, seed);
     let mut buffer = vec![0u8; block_size];
 
     // first create/fill the file
     for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
         for (j, x) in
             (&mut prng)
-                .take(usize::try_from(
-                    min(i+u64::try_from(block_size).unwrap(), size) - i
-                ).unwrap())
+                .take(step_size)
                 .enumerate()
         {
             buffer[j] = x as u8;
         }
+        mark(config, &mut buffer);
 
-        file.write_all(&buffer).unwrap();
+        with_retry(retries, || file.write_all(&buffer[..step_size])).unwrap();
     }
 
     mem::drop(file);
-    let mut file = BufReader::new(File::open(&path).unwrap());
+    let mut file = BufReader::with_capacity(
+        buf_capacity,
+        with_retry(retries, || File::open(&path)).unwrap()
+    );
 
     // Now measure reads
     let stopwatch = Instant::now();
 
-    // this division is a workaround for Range<u64> limitations
+    // this division is a workaround for Range<u64> limitations, rounded
+    // up so a trailing partial block is included
     for i in
-        (0..size/u64::try_from(block_size).unwrap())
+        (0..size.div_ceil(u64::try_from(block_size).unwrap()))
             .rev()
             .map(|i| i*u64::try_from(block_size).unwrap())
     {
@@ -353,7 +474,7 @@ pub fn read_reversed(size: u64, block_size: usize, run: u32) -> Duration {
         hint::black_box({
             file.seek(SeekFrom::Start(i)).unwrap();
 
-            file.read_exact(hint::black_box(&mut buffer[..step_size])).unwrap();
+            with_retry(retries, || file.read_exact(hint::black_box(&mut buffer[..step_size]))).unwrap();
             &buffer
         });
     }
@@ -361,50 +482,56 @@ pub fn read_reversed(size: u64, block_size: usize, run: u32) -> Duration {
     let duration = stopwatch.elapsed();
 
     mem::drop(file);
-    let file = File::create(&path).unwrap();
+    let file = with_retry(retries, || File::create(&path)).unwrap();
 
     // Truncate the file! Otherwise Veracruz may try to copy it back over
     // into the user's fs, which is a waste of (significant) time...
     //
     file.set_len(0).unwrap();
 
-    duration
+    Report::new(duration)
+        .with("buf_capacity", buf_capacity)
 }
 
 /// Write a large file in reverse-order
-pub fn write_random(size: u64, block_size: usize, run: u32) -> Duration {
-    let path = format!("/scratch/buffered_write_random_{}_{}_{}.txt", size, block_size, run);
-    let mut file = BufWriter::new(File::create(&path).unwrap());
-    let prng = RefCell::new(xorshift64(42));
+pub fn write_random(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/buffered_write_random_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
+    let prng = RefCell::new(Prng::new(config.prng, seed));
     let mut buffer = vec![0u8; block_size];
 
     let stopwatch = Instant::now();
 
     // this may not touch every block, but that's ok
     let count = size/u64::try_from(block_size).unwrap();
+    let mut coverage = Coverage::new(count);
     for i in 
         (0..count)
             .map(|_| prng.borrow_mut().next().unwrap() % count)
             .map(|i| i*u64::try_from(block_size).unwrap())
     {
+        coverage.touch(i/u64::try_from(block_size).unwrap());
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
         for (j, x) in
             prng
                 .borrow_mut()
                 .deref_mut()
-                .take(usize::try_from(
-                    min(i+u64::try_from(block_size).unwrap(), size) - i
-                ).unwrap())
+                .take(step_size)
                 .enumerate()
         {
             buffer[j] = x as u8;
         }
+        mark(config, &mut buffer);
 
         
         hint::black_box({
             file.seek(SeekFrom::Start(i)).unwrap();
 
-            let input = hint::black_box(&buffer);
-            file.write_all(input).unwrap();
+            let input = hint::black_box(&buffer[..step_size]);
+            with_retry(retries, || file.write_all(input)).unwrap();
         });
     }
 
@@ -414,70 +541,81 @@ pub fn write_random(size: u64, block_size: usize, run: u32) -> Duration {
 
     let duration = stopwatch.elapsed();
 
+    let metadata = file.get_ref().metadata().unwrap();
+
     // Truncate the file! Otherwise Veracruz may try to copy it back over
     // into the user's fs, which is a waste of (significant) time...
     //
     let file = file.into_inner().unwrap();
     file.set_len(0).unwrap();
 
-    duration
+    Report::new(duration)
+        .with_coverage(&coverage)
+        .with_footprint(&metadata)
 }
 
 /// Update a large file in reverse-order
-pub fn update_random(size: u64, block_size: usize, run: u32) -> Duration {
-    let path = format!("/scratch/buffered_update_random_{}_{}_{}.txt", size, block_size, run);
-    let mut file = BufWriter::new(File::create(&path).unwrap());
-    let prng = RefCell::new(xorshift64(42));
+pub fn update_random(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/buffered_update_random_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
+    let prng = RefCell::new(Prng::new(config.prng, seed));
     let mut buffer = vec![0u8; block_size];
 
     // first create/fill the file
     for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
         for (j, x) in
             prng
                 .borrow_mut()
                 .deref_mut()
-                .take(usize::try_from(
-                    min(i+u64::try_from(block_size).unwrap(), size) - i
-                ).unwrap())
+                .take(step_size)
                 .enumerate()
         {
             buffer[j] = x as u8;
         }
+        mark(config, &mut buffer);
 
-        file.write_all(&buffer).unwrap();
+        with_retry(retries, || file.write_all(&buffer[..step_size])).unwrap();
     }
 
     mem::drop(file);
-    let mut file = BufWriter::new(File::create(&path).unwrap());
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
 
     // now measure updates
     let stopwatch = Instant::now();
 
     // this may not touch every block, but that's ok
     let count = size/u64::try_from(block_size).unwrap();
+    let mut coverage = Coverage::new(count);
     for i in 
         (0..count)
             .map(|_| prng.borrow_mut().next().unwrap() % count)
             .map(|i| i*u64::try_from(block_size).unwrap())
     {
+        coverage.touch(i/u64::try_from(block_size).unwrap());
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
         for (j, x) in
             prng
                 .borrow_mut()
                 .deref_mut()
-                .take(usize::try_from(
-                    min(i+u64::try_from(block_size).unwrap(), size) - i
-                ).unwrap())
+                .take(step_size)
                 .enumerate()
         {
             buffer[j] = x as u8;
         }
+        mark(config, &mut buffer);
 
         
         hint::black_box({
             file.seek(SeekFrom::Start(i)).unwrap();
 
-            let input = hint::black_box(&buffer);
-            file.write_all(input).unwrap();
+            let input = hint::black_box(&buffer[..step_size]);
+            with_retry(retries, || file.write_all(input)).unwrap();
         });
     }
 
@@ -493,44 +631,58 @@ pub fn update_random(size: u64, block_size: usize, run: u32) -> Duration {
     let file = file.into_inner().unwrap();
     file.set_len(0).unwrap();
 
-    duration
+    Report::new(duration)
+        .with_coverage(&coverage)
 }
 
 /// Read a large file in reverse-order
-pub fn read_random(size: u64, block_size: usize, run: u32) -> Duration {
-    let path = format!("/scratch/buffered_read_random_{}_{}_{}.txt", size, block_size, run);
-    let mut file = BufWriter::new(File::create(&path).unwrap());
-    let mut prng = xorshift64(42);
+pub fn read_random(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, warmup, buf_capacity, .. } = *config;
+    let path = format!("{}/buffered_read_random_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
+    let mut prng = Prng::new(config.prng, seed);
     let mut buffer = vec![0u8; block_size];
 
     // first create/fill the file
     for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
         for (j, x) in
             (&mut prng)
-                .take(usize::try_from(
-                    min(i+u64::try_from(block_size).unwrap(), size) - i
-                ).unwrap())
+                .take(step_size)
                 .enumerate()
         {
             buffer[j] = x as u8;
         }
+        mark(config, &mut buffer);
 
-        file.write_all(&buffer).unwrap();
+        with_retry(retries, || file.write_all(&buffer[..step_size])).unwrap();
     }
 
     mem::drop(file);
-    let mut file = BufReader::new(File::open(&path).unwrap());
+    let mut file = BufReader::with_capacity(
+        buf_capacity,
+        with_retry(retries, || File::open(&path)).unwrap()
+    );
+
+    // optionally read everything once first, so we measure warm reads
+    if warmup {
+        warm_up(&mut file, &mut buffer, retries);
+    }
 
     // Now measure reads
     let stopwatch = Instant::now();
 
     // this may not touch every block, but that's ok
     let count = size/u64::try_from(block_size).unwrap();
+    let mut coverage = Coverage::new(count);
     for i in 
         (0..count)
-            .map(|_| (&mut prng).next().unwrap() % count)
+            .map(|_| prng.next().unwrap() % count)
             .map(|i| i*u64::try_from(block_size).unwrap())
     {
+        coverage.touch(i/u64::try_from(block_size).unwrap());
         let step_size = usize::try_from(
             min(i+u64::try_from(block_size).unwrap(), size) - i
         ).unwrap();
@@ -538,7 +690,7 @@ pub fn read_random(size: u64, block_size: usize, run: u32) -> Duration {
         hint::black_box({
             file.seek(SeekFrom::Start(i)).unwrap();
 
-            file.read_exact(hint::black_box(&mut buffer[..step_size])).unwrap();
+            with_retry(retries, || file.read_exact(hint::black_box(&mut buffer[..step_size]))).unwrap();
             &buffer
         });
     }
@@ -546,12 +698,207 @@ pub fn read_random(size: u64, block_size: usize, run: u32) -> Duration {
     let duration = stopwatch.elapsed();
 
     mem::drop(file);
-    let file = File::create(&path).unwrap();
+    let file = with_retry(retries, || File::create(&path)).unwrap();
+
+    // Truncate the file! Otherwise Veracruz may try to copy it back over
+    // into the user's fs, which is a waste of (significant) time...
+    //
+    file.set_len(0).unwrap();
+
+    Report::new(duration)
+        .with_coverage(&coverage)
+        .with("buf_capacity", buf_capacity)
+        .with("warmup", warmup)
+}
+
+/// How buffered_read_seek_inorder repositions before each block
+#[derive(Clone, Copy)]
+enum Reposition {
+    None,
+    Seek,
+    SeekRelative,
+}
+
+/// Read a large file in-order through a BufReader, seeking to each block
+/// before reading it
+///
+/// The seeks land where the reader already is, so the access pattern is the
+/// same as buffered_read_inorder, but BufReader::seek discards the buffer
+/// every time. The same reads without seeking, and with seek_relative which
+/// keeps the buffer, are measured for comparison, isolating what buffer
+/// invalidation costs.
+pub fn read_seek_inorder(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, buf_capacity, .. } = *config;
+    let path = format!("{}/buffered_read_seek_inorder_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
+    let mut prng = Prng::new(config.prng, seed);
+    let mut buffer = vec![0u8; block_size];
+
+    // first create/fill the file
+    for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
+        for (j, x) in
+            (&mut prng)
+                .take(step_size)
+                .enumerate()
+        {
+            buffer[j] = x as u8;
+        }
+        mark(config, &mut buffer);
+
+        with_retry(retries, || file.write_all(&buffer[..step_size])).unwrap();
+    }
+
+    mem::drop(file);
+
+    let mut measure = |reposition: Reposition| {
+        let mut file = BufReader::with_capacity(
+            buf_capacity,
+            with_retry(retries, || File::open(&path)).unwrap()
+        );
+
+        let stopwatch = Instant::now();
+
+        for i in (0..size).step_by(block_size) {
+            let step_size = usize::try_from(
+                min(i+u64::try_from(block_size).unwrap(), size) - i
+            ).unwrap();
+
+            hint::black_box({
+                match reposition {
+                    Reposition::None => {}
+                    Reposition::Seek => {
+                        file.seek(SeekFrom::Start(i)).unwrap();
+                    }
+                    Reposition::SeekRelative => {
+                        file.seek_relative(0).unwrap();
+                    }
+                }
+
+                with_retry(retries, || file.read_exact(hint::black_box(&mut buffer[..step_size]))).unwrap();
+                &buffer
+            });
+        }
+
+        stopwatch.elapsed()
+    };
+
+    // Now measure reads, with each kind of repositioning
+    let seek_duration = measure(Reposition::Seek);
+    let noseek_duration = measure(Reposition::None);
+    let seek_relative_duration = measure(Reposition::SeekRelative);
+
+    let file = with_retry(retries, || File::create(&path)).unwrap();
+
+    // Truncate the file! Otherwise Veracruz may try to copy it back over
+    // into the user's fs, which is a waste of (significant) time...
+    //
+    file.set_len(0).unwrap();
+
+    Report::new(seek_duration)
+        .with("buf_capacity", buf_capacity)
+        .with("noseek_runtime", noseek_duration.as_secs_f64())
+        .with("seek_relative_runtime", seek_relative_duration.as_secs_f64())
+        .with("invalidation_ratio",
+            seek_duration.as_secs_f64() / noseek_duration.as_secs_f64()
+        )
+}
+
+/// Read random blocks of a large file through a BufReader, seeking to each
+/// block before reading it, compared against reading as many blocks in-order
+/// through the same kind of BufReader
+///
+/// Each seek discards the buffer, and the read after it refills the buffer
+/// from the new position, so with random seeks most of what is buffered is
+/// never used. The in-order reads are measured first, the gap between the
+/// two is what buffer invalidation costs.
+pub fn read_seek_random(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, buf_capacity, .. } = *config;
+    let path = format!("{}/buffered_read_seek_random_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
+    let mut prng = Prng::new(config.prng, seed);
+    let mut buffer = vec![0u8; block_size];
+
+    // first create/fill the file
+    for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
+        for (j, x) in
+            (&mut prng)
+                .take(step_size)
+                .enumerate()
+        {
+            buffer[j] = x as u8;
+        }
+        mark(config, &mut buffer);
+
+        with_retry(retries, || file.write_all(&buffer[..step_size])).unwrap();
+    }
+
+    mem::drop(file);
+
+    // only whole blocks are read, so both passes read the same amount
+    let count = size/u64::try_from(block_size).unwrap();
+    let order = (0..count)
+        .map(|_| prng.next().unwrap() % count)
+        .collect::<Vec<_>>();
+
+    let mut measure = |seek: bool| {
+        let mut file = BufReader::with_capacity(
+            buf_capacity,
+            with_retry(retries, || File::open(&path)).unwrap()
+        );
+
+        let stopwatch = Instant::now();
+
+        for &index in &order {
+            hint::black_box({
+                if seek {
+                    file.seek(SeekFrom::Start(index*u64::try_from(block_size).unwrap())).unwrap();
+                }
+
+                with_retry(retries, || file.read_exact(hint::black_box(&mut buffer))).unwrap();
+                &buffer
+            });
+        }
+
+        stopwatch.elapsed()
+    };
+
+    // Now measure reads, in-order then random
+    let sequential_duration = measure(false);
+    let duration = measure(true);
+
+    let file = with_retry(retries, || File::create(&path)).unwrap();
 
     // Truncate the file! Otherwise Veracruz may try to copy it back over
     // into the user's fs, which is a waste of (significant) time...
     //
     file.set_len(0).unwrap();
 
-    duration
+    Report::new(duration)
+        .with("buf_capacity", buf_capacity)
+        .with("sequential_runtime", sequential_duration.as_secs_f64())
+        .with("random_vs_sequential_ratio",
+            duration.as_secs_f64() / sequential_duration.as_secs_f64()
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::assert_partial_block;
+
+    #[test]
+    fn write_inorder_partial_block() {
+        assert_partial_block("buffered_write_inorder", write_inorder);
+    }
+
+    #[test]
+    fn write_reversed_partial_block() {
+        assert_partial_block("buffered_write_reversed", write_reversed);
+    }
 }
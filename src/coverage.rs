@@ -0,0 +1,51 @@
+//! Tracking which blocks a random access pattern touched
+//!
+//! ## Authors
+//!
+//! The Veracruz Development Team.
+//!
+//! ## Copyright
+//!
+//! See the file `LICENSING.markdown` in the Veracruz root directory for licensing
+//! and copyright information.
+
+use std::convert::TryFrom;
+
+/// A bitset of the block indices visited by one of the random modes
+///
+/// Those draw count indices with replacement, so some blocks are visited
+/// more than once and others not at all. This shows how many were actually
+/// covered, so random-mode numbers can be read with that in mind.
+pub struct Coverage {
+    touched: Vec<u64>,
+    count: u64,
+}
+
+impl Coverage {
+    pub fn new(count: u64) -> Self {
+        Self {
+            touched: vec![0; usize::try_from(count.div_ceil(64)).unwrap()],
+            count,
+        }
+    }
+
+    /// Mark a block index as touched, this is a single bit operation so it
+    /// can go in a timed loop
+    pub fn touch(&mut self, index: u64) {
+        self.touched[usize::try_from(index / 64).unwrap()] |= 1 << (index % 64);
+    }
+
+    /// Number of distinct blocks touched
+    pub fn unique(&self) -> u64 {
+        self.touched.iter().map(|word| u64::from(word.count_ones())).sum()
+    }
+
+    /// Fraction of the blocks touched at least once, zero if the file is
+    /// smaller than one block and so has none
+    pub fn fraction(&self) -> f64 {
+        if self.count == 0 {
+            return 0.0;
+        }
+        self.unique() as f64 / self.count as f64
+    }
+}
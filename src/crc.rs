@@ -0,0 +1,72 @@
+//! Self-verifying blocks, with a CRC32 stored inline in each block
+//!
+//! ## Authors
+//!
+//! The Veracruz Development Team.
+//!
+//! ## Copyright
+//!
+//! See the file `LICENSING.markdown` in the Veracruz root directory for licensing
+//! and copyright information.
+
+use crate::{
+    Config,
+    retry::with_retry,
+};
+use std::{
+    cmp::min,
+    convert::TryFrom,
+    fs::File,
+    io::Read,
+    time::Duration,
+    time::Instant,
+};
+
+/// Bytes at the start of each block reserved for its CRC
+const CRC_SIZE: usize = 4;
+
+/// With --inline-crc, overwrite the first 4 bytes of a freshly filled
+/// block with a CRC32 of the rest of it, so corruption of any single block
+/// can be detected on read without regenerating the PRNG stream
+///
+/// Blocks too small to hold a CRC are left alone. This returns how long
+/// the CRC took, so timed loops can take it out of their measurement.
+pub fn stamp(config: &Config, block: &mut [u8]) -> Duration {
+    if !config.inline_crc || block.len() <= CRC_SIZE {
+        return Duration::ZERO;
+    }
+
+    let stopwatch = Instant::now();
+    let crc = crc32fast::hash(&block[CRC_SIZE..]);
+    block[..CRC_SIZE].copy_from_slice(&crc.to_le_bytes());
+    stopwatch.elapsed()
+}
+
+/// Read a whole file written with --inline-crc from its current position,
+/// checking every block's CRC, returning how many blocks were verified and
+/// the offset of the first mismatch, if any
+///
+/// As with stamp, blocks too small to hold a CRC are skipped.
+pub fn verify(config: &Config, file: &mut File) -> (u64, Option<u64>) {
+    let Config { size, block_size, retries, .. } = *config;
+    let mut buffer = vec![0u8; block_size];
+    let mut blocks = 0u64;
+
+    for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
+        let block = &mut buffer[..step_size];
+        with_retry(retries, || file.read_exact(block)).unwrap();
+
+        if block.len() > CRC_SIZE {
+            let crc = crc32fast::hash(&block[CRC_SIZE..]);
+            if block[..CRC_SIZE] != crc.to_le_bytes() {
+                return (blocks, Some(i));
+            }
+            blocks += 1;
+        }
+    }
+
+    (blocks, None)
+}
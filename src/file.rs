@@ -9,370 +9,826 @@
 //! See the file `LICENSING.markdown` in the Veracruz root directory for licensing
 //! and copyright information.
 
+use crate::{
+    Config,
+    hint,
+    marker::mark,
+    crc,
+    block_timings::BlockTimings,
+    coverage::Coverage,
+    sequence::{Op, Sequence},
+    report::Report,
+    report::supported,
+    retry::with_retry,
+    short_read::read_block,
+    rng::Prng,
+    interrupt::interrupted,
+    think::think,
+    threads::{timed_parallel, SetOnDrop},
+};
 use std::{
     cell::RefCell,
     cmp::min,
     convert::TryFrom,
     fs::File,
-    hint,
+    fs::OpenOptions,
+    io,
     io::Write,
     io::Read,
     io::Seek,
     io::SeekFrom,
     io::BufWriter,
-    iter,
     mem,
     ops::DerefMut,
+    sync::atomic::AtomicBool,
+    sync::atomic::AtomicU64,
+    sync::atomic::Ordering,
+    thread,
     time::Duration,
     time::Instant,
 };
 
-/// xorshift64 for providing deterministic pseudo-random numbers
-fn xorshift64(seed: u64) -> impl Iterator<Item=u64> {
-    let mut x = seed;
-    iter::repeat_with(move || {
-        x ^= x << 13;
-        x ^= x >> 7;
-        x ^= x << 17;
-        x
-    })
+/// Size of the first write in write_ramp, doubling from there
+const RAMP_START: usize = 512;
+
+
+/// Sequentially read a whole file once, to populate any caches before a
+/// measurement
+pub fn warm_up(file: &mut impl Read, buffer: &mut [u8], retries: u32) {
+    loop {
+        let n = hint::black_box(
+            with_retry(retries, || file.read(hint::black_box(&mut *buffer))).unwrap()
+        );
+        if n == 0 {
+            break;
+        }
+    }
+}
+
+
+/// Read a whole file in-order from its current position, each block --reuse
+/// times, returning how long that took and how many blocks were read before
+/// any interruption
+fn measure_read_inorder(
+    config: &Config,
+    file: &mut File,
+    buffer: &mut [u8]
+) -> (Duration, u64) {
+    let Config { size, block_size, retries, reuse, .. } = *config;
+    let mut blocks = 0u64;
+    let mut paused = Duration::ZERO;
+    let stopwatch = Instant::now();
+
+    for i in (0..size).step_by(block_size) {
+        if interrupted() || config.capped(blocks) {
+            break;
+        }
+        paused += think(config);
+
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
+        
+        hint::black_box({
+            for k in 0..reuse {
+                // step back to re-read the same block
+                if k > 0 {
+                    file.seek(SeekFrom::Current(-i64::try_from(step_size).unwrap())).unwrap();
+                }
+
+                read_block(retries, file, hint::black_box(&mut buffer[..step_size]), i/u64::try_from(block_size).unwrap(), i);
+            }
+            &buffer
+        });
+        blocks += 1;
+    }
+
+    (stopwatch.elapsed() - paused, blocks)
+}
+
+/// Read a file's worth of blocks in random-order, each block --reuse times,
+/// returning how long that took and how many blocks were read before any
+/// interruption
+///
+/// The blocks read are recorded in sequence and marked in coverage.
+fn measure_read_random(
+    config: &Config,
+    file: &mut File,
+    prng: &mut impl Iterator<Item=u64>,
+    buffer: &mut [u8],
+    sequence: &mut Sequence,
+    coverage: &mut Coverage,
+) -> (Duration, u64) {
+    let Config { size, block_size, retries, reuse, .. } = *config;
+    let mut blocks = 0u64;
+    let mut paused = Duration::ZERO;
+    let stopwatch = Instant::now();
+
+    // this may not touch every block, but that's ok
+    let count = size/u64::try_from(block_size).unwrap();
+    for i in 
+        (0..count)
+            .map(|_| prng.next().unwrap() % count)
+            .map(|i| i*u64::try_from(block_size).unwrap())
+    {
+        if interrupted() || config.capped(blocks) {
+            break;
+        }
+        paused += think(config);
+
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
+        coverage.touch(i/u64::try_from(block_size).unwrap());
+        sequence.record(i/u64::try_from(block_size).unwrap(), Op::Read);
+        
+        hint::black_box({
+            for _ in 0..reuse {
+                file.seek(SeekFrom::Start(i)).unwrap();
+
+                read_block(retries, file, hint::black_box(&mut buffer[..step_size]), i/u64::try_from(block_size).unwrap(), i);
+            }
+            &buffer
+        });
+        blocks += 1;
+    }
+
+    (stopwatch.elapsed() - paused, blocks)
 }
 
 
 /// Write a large file in-order
-pub fn write_inorder(size: u64, block_size: usize, run: u32) -> Duration {
-    let path = format!("/scratch/write_inorder_{}_{}_{}.txt", size, block_size, run);
-    let mut file = File::create(&path).unwrap();
-    let mut prng = xorshift64(42);
+pub fn write_inorder(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/write_inorder_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = with_retry(retries, || File::create(&path)).unwrap();
+    let mut prng = Prng::new(config.prng, seed);
     let mut buffer = vec![0u8; block_size];
 
+    let mut timings = BlockTimings::new(config, size/u64::try_from(block_size).unwrap());
+    let mut blocks = 0u64;
+    let mut paused = Duration::ZERO;
     let stopwatch = Instant::now();
 
     for i in (0..size).step_by(block_size) {
+        if interrupted() || config.capped(blocks) {
+            break;
+        }
+        paused += think(config);
+
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
         for (j, x) in
             (&mut prng)
-                .take(usize::try_from(
-                    min(i+u64::try_from(block_size).unwrap(), size) - i
-                ).unwrap())
+                .take(step_size)
                 .enumerate()
         {
             buffer[j] = x as u8;
         }
-
-        
-        hint::black_box({
-            let input = hint::black_box(&buffer);
-            file.write_all(input).unwrap();
-        });
+        mark(config, &mut buffer);
+        paused += crc::stamp(config, &mut buffer[..step_size]);
+
+        timings.time(i, || hint::black_box({
+            let input = hint::black_box(&buffer[..step_size]);
+            with_retry(retries, || file.write_all(input)).unwrap();
+        }));
+        blocks += 1;
     }
 
     hint::black_box({
         file.flush().unwrap();
+        supported!(config.sync_mode.sync(&file, retries), file.set_len(0).unwrap());
     });
 
-    let duration = stopwatch.elapsed();
+    let duration = stopwatch.elapsed() - paused;
+    timings.write_csv(config);
+
+    let metadata = file.metadata().unwrap();
+
+    // Truncate the file! Otherwise Veracruz may try to copy it back over
+    // into the user's fs, which is a waste of (significant) time...
+    //
+    file.set_len(0).unwrap();
+
+    Report::new(duration)
+        .with_progress(config, blocks)
+        .with_footprint(&metadata)
+        .with("inline_crc", config.inline_crc)
+}
+
+/// Write a large file in-order with one write call per block, compared
+/// against the same writes through write_all
+///
+/// Each write's returned count is checked, so we can see whether the VFS
+/// accepts a whole block in one call, which write_all would hide. A short
+/// write is counted and the rest of its block written by further calls,
+/// which are also timed, as code looping over write itself does.
+pub fn write_single(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/write_single_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut buffer = vec![0u8; block_size];
+
+    let mut short_writes = 0u64;
+    let mut calls = 0u64;
+    let mut measure = |single: bool| {
+        // both passes write the same data
+        let mut prng = Prng::new(config.prng, seed);
+        let mut file = with_retry(retries, || File::create(&path)).unwrap();
+        let mut blocks = 0u64;
+        let stopwatch = Instant::now();
+
+        for i in (0..size).step_by(block_size) {
+            if interrupted() {
+                break;
+            }
+
+            let step_size = usize::try_from(
+                min(i+u64::try_from(block_size).unwrap(), size) - i
+            ).unwrap();
+            for (j, x) in
+                (&mut prng)
+                    .take(step_size)
+                    .enumerate()
+            {
+                buffer[j] = x as u8;
+            }
+            mark(config, &mut buffer);
+
+            hint::black_box({
+                let mut input = hint::black_box(&buffer[..step_size]);
+                if single {
+                    while !input.is_empty() {
+                        let n = with_retry(retries, || file.write(input)).unwrap();
+                        assert!(n > 0, "write of {} bytes wrote nothing", input.len());
+                        if n < input.len() {
+                            short_writes += 1;
+                        }
+                        input = &input[n..];
+                        calls += 1;
+                    }
+                } else {
+                    with_retry(retries, || file.write_all(input)).unwrap();
+                }
+            });
+            blocks += 1;
+        }
+
+        hint::black_box({
+            file.flush().unwrap();
+        });
+
+        let duration = stopwatch.elapsed();
+
+        // Truncate the file! Otherwise Veracruz may try to copy it back over
+        // into the user's fs, which is a waste of (significant) time...
+        //
+        file.set_len(0).unwrap();
+
+        (duration, blocks)
+    };
+
+    // first measure single writes, then write_all
+    let (duration, blocks) = measure(true);
+    let (write_all_duration, _) = measure(false);
+
+    Report::new(duration)
+        .with_progress(config, blocks)
+        .with("write_calls", calls)
+        .with("short_writes", short_writes)
+        .with("short_write_rate", short_writes as f64 / calls.max(1) as f64)
+        .with("write_all_runtime", write_all_duration.as_secs_f64())
+}
+
+/// Grow a large file one byte per write call
+///
+/// This is the pathological appender, so the throughput is tiny, but it
+/// gives the floor of the per-call cost. The footprint shows any write
+/// amplification from the VFS allocating for each tiny write. block_size
+/// only sets how many bytes are generated between writes, and the unit of
+/// --max-ops.
+pub fn byte_append(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/byte_append_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = with_retry(retries, || File::create(&path)).unwrap();
+    let mut prng = Prng::new(config.prng, seed);
+    let mut buffer = vec![0u8; block_size];
+
+    let mut blocks = 0u64;
+    let mut bytes = 0u64;
+    let mut paused = Duration::ZERO;
+    let stopwatch = Instant::now();
+
+    for i in (0..size).step_by(block_size) {
+        if interrupted() || config.capped(blocks) {
+            break;
+        }
+        paused += think(config);
+
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
+        for (j, x) in
+            (&mut prng)
+                .take(step_size)
+                .enumerate()
+        {
+            buffer[j] = x as u8;
+        }
+        mark(config, &mut buffer);
+
+        hint::black_box({
+            for j in 0..step_size {
+                let input = hint::black_box(&buffer[j..j+1]);
+                with_retry(retries, || file.write_all(input)).unwrap();
+            }
+        });
+        bytes += u64::try_from(step_size).unwrap();
+        blocks += 1;
+    }
+
+    let duration = stopwatch.elapsed() - paused;
+
+    let metadata = file.metadata().unwrap();
 
     // Truncate the file! Otherwise Veracruz may try to copy it back over
     // into the user's fs, which is a waste of (significant) time...
     //
     file.set_len(0).unwrap();
 
-    duration
+    Report::new(duration)
+        .with_progress(config, blocks)
+        .with_footprint(&metadata)
+        .with("write_calls", bytes)
+        .with("bytes_per_sec", bytes as f64 / duration.as_secs_f64())
+        .with("seconds_per_call",
+            if bytes > 0 { duration.as_secs_f64() / bytes as f64 } else { 0.0 }
+        )
 }
 
 /// Update a large file in-order
-pub fn update_inorder(size: u64, block_size: usize, run: u32) -> Duration {
-    let path = format!("/scratch/update_inorder_{}_{}_{}.txt", size, block_size, run);
-    let mut file = BufWriter::new(File::create(&path).unwrap());
-    let mut prng = xorshift64(42);
+pub fn update_inorder(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/update_inorder_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
+    let mut prng = Prng::new(config.prng, seed);
     let mut buffer = vec![0u8; block_size];
 
     // first create/fill the file
     for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
         for (j, x) in
             (&mut prng)
-                .take(usize::try_from(
-                    min(i+u64::try_from(block_size).unwrap(), size) - i
-                ).unwrap())
+                .take(step_size)
                 .enumerate()
         {
             buffer[j] = x as u8;
         }
+        mark(config, &mut buffer);
 
-        file.write_all(&buffer).unwrap();
+        with_retry(retries, || file.write_all(&buffer[..step_size])).unwrap();
     }
 
     mem::drop(file);
-    let mut file = File::create(&path).unwrap();
+    let mut file = with_retry(retries, || File::create(&path)).unwrap();
 
     // now measure updates
+    let mut blocks = 0u64;
+    let mut paused = Duration::ZERO;
     let stopwatch = Instant::now();
 
     for i in (0..size).step_by(block_size) {
+        if interrupted() || config.capped(blocks) {
+            break;
+        }
+        paused += think(config);
+
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
         for (j, x) in
             (&mut prng)
-                .take(usize::try_from(
-                    min(i+u64::try_from(block_size).unwrap(), size) - i
-                ).unwrap())
+                .take(step_size)
                 .enumerate()
         {
             buffer[j] = x as u8;
         }
+        mark(config, &mut buffer);
 
         
         hint::black_box({
-            let input = hint::black_box(&buffer);
-            file.write_all(input).unwrap();
+            let input = hint::black_box(&buffer[..step_size]);
+            with_retry(retries, || file.write_all(input)).unwrap();
         });
+        blocks += 1;
     }
 
     hint::black_box({
         file.flush().unwrap();
+        supported!(config.sync_mode.sync(&file, retries), file.set_len(0).unwrap());
     });
 
-    let duration = stopwatch.elapsed();
+    let duration = stopwatch.elapsed() - paused;
 
     // Truncate the file! Otherwise Veracruz may try to copy it back over
     // into the user's fs, which is a waste of (significant) time...
     //
     file.set_len(0).unwrap();
 
-    duration
+    Report::new(duration)
+        .with_progress(config, blocks)
 }
 
 /// Read a large file in-order
-pub fn read_inorder(size: u64, block_size: usize, run: u32) -> Duration {
-    let path = format!("/scratch/read_inorder_{}_{}_{}.txt", size, block_size, run);
-    let mut file = BufWriter::new(File::create(&path).unwrap());
-    let mut prng = xorshift64(42);
+pub fn read_inorder(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, reuse, .. } = *config;
+    let path = format!("{}/read_inorder_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
+    let mut prng = Prng::new(config.prng, seed);
     let mut buffer = vec![0u8; block_size];
 
     // first create/fill the file
     for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
         for (j, x) in
             (&mut prng)
-                .take(usize::try_from(
-                    min(i+u64::try_from(block_size).unwrap(), size) - i
-                ).unwrap())
+                .take(step_size)
                 .enumerate()
         {
             buffer[j] = x as u8;
         }
+        mark(config, &mut buffer);
+        crc::stamp(config, &mut buffer[..step_size]);
 
-        file.write_all(&buffer).unwrap();
+        with_retry(retries, || file.write_all(&buffer[..step_size])).unwrap();
     }
 
     mem::drop(file);
-    let mut file = File::open(&path).unwrap();
+    let mut file = with_retry(retries, || File::open(&path)).unwrap();
 
     // Now measure reads
+    let (duration, blocks) = measure_read_inorder(config, &mut file, &mut buffer);
+
+    // then, untimed, check every block's CRC
+    let verified = if config.inline_crc {
+        file.seek(SeekFrom::Start(0)).unwrap();
+        Some(crc::verify(config, &mut file))
+    } else {
+        None
+    };
+
+    mem::drop(file);
+    let file = with_retry(retries, || File::create(&path)).unwrap();
+
+    // Truncate the file! Otherwise Veracruz may try to copy it back over
+    // into the user's fs, which is a waste of (significant) time...
+    //
+    file.set_len(0).unwrap();
+
+    let report = Report::new(duration)
+        .with_progress(config, blocks)
+        .with("reuse", reuse)
+        .with("bytes_per_sec",
+            (blocks*u64::from(reuse)*u64::try_from(block_size).unwrap()) as f64
+                / duration.as_secs_f64()
+        );
+
+    match verified {
+        Some((verified, mismatch)) => report
+            .with("crc_blocks_verified", verified)
+            .with("crc_mismatch_offset", mismatch),
+        None => report,
+    }
+}
+
+/// Read a large file in-order with several read_exact calls of --read-chunk
+/// bytes per block, compared against one read_exact per block
+///
+/// The read-side counterpart of write_records, the gap between the two
+/// runtimes is the cost of the extra read calls, which is reported per
+/// call.
+pub fn read_chunks(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, read_chunk, .. } = *config;
+    let path = format!("{}/read_chunks_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
+    let mut prng = Prng::new(config.prng, seed);
+    let mut buffer = vec![0u8; block_size];
+
+    // first create/fill the file
+    for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
+        for (j, x) in
+            (&mut prng)
+                .take(step_size)
+                .enumerate()
+        {
+            buffer[j] = x as u8;
+        }
+        mark(config, &mut buffer);
+
+        with_retry(retries, || file.write_all(&buffer[..step_size])).unwrap();
+    }
+
+    mem::drop(file);
+    let mut file = with_retry(retries, || File::open(&path)).unwrap();
+
+    // Now measure chunked reads
+    let mut calls = 0u64;
+    let mut blocks = 0u64;
     let stopwatch = Instant::now();
 
     for i in (0..size).step_by(block_size) {
+        if interrupted() || config.capped(blocks) {
+            break;
+        }
+
         let step_size = usize::try_from(
             min(i+u64::try_from(block_size).unwrap(), size) - i
         ).unwrap();
-        
+
         hint::black_box({
-            file.read_exact(hint::black_box(&mut buffer[..step_size])).unwrap();
+            for chunk in buffer[..step_size].chunks_mut(read_chunk) {
+                with_retry(retries, || file.read_exact(hint::black_box(&mut *chunk))).unwrap();
+                calls += 1;
+            }
             &buffer
         });
+        blocks += 1;
     }
 
     let duration = stopwatch.elapsed();
 
+    // and whole blocks
+    file.seek(SeekFrom::Start(0)).unwrap();
+    let (block_duration, _) = measure_read_inorder(config, &mut file, &mut buffer);
+
     mem::drop(file);
-    let file = File::create(&path).unwrap();
+    let file = with_retry(retries, || File::create(&path)).unwrap();
 
     // Truncate the file! Otherwise Veracruz may try to copy it back over
     // into the user's fs, which is a waste of (significant) time...
     //
     file.set_len(0).unwrap();
 
-    duration
+    Report::new(duration)
+        .with_progress(config, blocks)
+        .with("read_chunk", u64::try_from(read_chunk).unwrap())
+        .with("read_calls", calls)
+        .with("block_read_runtime", block_duration.as_secs_f64())
+        .with("seconds_per_extra_call",
+            (duration.as_secs_f64() - block_duration.as_secs_f64())
+                / calls.saturating_sub(blocks).max(1) as f64
+        )
 }
 
 /// Write a large file in reverse-order
-pub fn write_reversed(size: u64, block_size: usize, run: u32) -> Duration {
-    let path = format!("/scratch/write_reversed_{}_{}_{}.txt", size, block_size, run);
-    let mut file = File::create(&path).unwrap();
-    let mut prng = xorshift64(42);
+pub fn write_reversed(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/write_reversed_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = with_retry(retries, || File::create(&path)).unwrap();
+    let mut prng = Prng::new(config.prng, seed);
     let mut buffer = vec![0u8; block_size];
 
+    let mut timings = BlockTimings::new(config, size/u64::try_from(block_size).unwrap());
+    let mut blocks = 0u64;
+    let mut paused = Duration::ZERO;
     let stopwatch = Instant::now();
 
-    // this division is a workaround for Range<u64> limitations
+    // this division is a workaround for Range<u64> limitations, rounded
+    // up so a trailing partial block is included
     for i in
-        (0..size/u64::try_from(block_size).unwrap())
+        (0..size.div_ceil(u64::try_from(block_size).unwrap()))
             .rev()
             .map(|i| i*u64::try_from(block_size).unwrap())
     {
+        if interrupted() || config.capped(blocks) {
+            break;
+        }
+        paused += think(config);
+
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
         for (j, x) in
             (&mut prng)
-                .take(usize::try_from(
-                    min(i+u64::try_from(block_size).unwrap(), size) - i
-                ).unwrap())
+                .take(step_size)
                 .enumerate()
         {
             buffer[j] = x as u8;
         }
+        mark(config, &mut buffer);
 
         
-        hint::black_box({
+        timings.time(i, || hint::black_box({
             file.seek(SeekFrom::Start(i)).unwrap();
 
-            let input = hint::black_box(&buffer);
-            file.write_all(input).unwrap();
-        });
+            let input = hint::black_box(&buffer[..step_size]);
+            with_retry(retries, || file.write_all(input)).unwrap();
+        }));
+        blocks += 1;
     }
 
     hint::black_box({
         file.flush().unwrap();
+        supported!(config.sync_mode.sync(&file, retries), file.set_len(0).unwrap());
     });
 
-    let duration = stopwatch.elapsed();
+    let duration = stopwatch.elapsed() - paused;
+    timings.write_csv(config);
+
+    let metadata = file.metadata().unwrap();
 
     // Truncate the file! Otherwise Veracruz may try to copy it back over
     // into the user's fs, which is a waste of (significant) time...
     //
     file.set_len(0).unwrap();
 
-    duration
+    Report::new(duration)
+        .with_progress(config, blocks)
+        .with_footprint(&metadata)
 }
 
 /// Update a large file in reverse-order
-pub fn update_reversed(size: u64, block_size: usize, run: u32) -> Duration {
-    let path = format!("/scratch/update_reversed_{}_{}_{}.txt", size, block_size, run);
-    let mut file = BufWriter::new(File::create(&path).unwrap());
-    let mut prng = xorshift64(42);
+pub fn update_reversed(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/update_reversed_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
+    let mut prng = Prng::new(config.prng, seed);
     let mut buffer = vec![0u8; block_size];
 
     // first create/fill the file
     for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
         for (j, x) in
             (&mut prng)
-                .take(usize::try_from(
-                    min(i+u64::try_from(block_size).unwrap(), size) - i
-                ).unwrap())
+                .take(step_size)
                 .enumerate()
         {
             buffer[j] = x as u8;
         }
+        mark(config, &mut buffer);
 
-        file.write_all(&buffer).unwrap();
+        with_retry(retries, || file.write_all(&buffer[..step_size])).unwrap();
     }
 
     mem::drop(file);
-    let mut file = File::create(&path).unwrap();
+    let mut file = with_retry(retries, || File::create(&path)).unwrap();
 
     // now measure updates
+    let mut blocks = 0u64;
+    let mut paused = Duration::ZERO;
     let stopwatch = Instant::now();
 
-    // this division is a workaround for Range<u64> limitations
+    // this division is a workaround for Range<u64> limitations, rounded
+    // up so a trailing partial block is included
     for i in
-        (0..size/u64::try_from(block_size).unwrap())
+        (0..size.div_ceil(u64::try_from(block_size).unwrap()))
             .rev()
             .map(|i| i*u64::try_from(block_size).unwrap())
     {
+        if interrupted() || config.capped(blocks) {
+            break;
+        }
+        paused += think(config);
+
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
         for (j, x) in
             (&mut prng)
-                .take(usize::try_from(
-                    min(i+u64::try_from(block_size).unwrap(), size) - i
-                ).unwrap())
+                .take(step_size)
                 .enumerate()
         {
             buffer[j] = x as u8;
         }
+        mark(config, &mut buffer);
 
         
         hint::black_box({
             file.seek(SeekFrom::Start(i)).unwrap();
 
-            let input = hint::black_box(&buffer);
-            file.write_all(input).unwrap();
+            let input = hint::black_box(&buffer[..step_size]);
+            with_retry(retries, || file.write_all(input)).unwrap();
         });
+        blocks += 1;
     }
 
     hint::black_box({
         file.flush().unwrap();
+        supported!(config.sync_mode.sync(&file, retries), file.set_len(0).unwrap());
     });
 
-    let duration = stopwatch.elapsed();
+    let duration = stopwatch.elapsed() - paused;
 
     // Truncate the file! Otherwise Veracruz may try to copy it back over
     // into the user's fs, which is a waste of (significant) time...
     //
     file.set_len(0).unwrap();
 
-    duration
+    Report::new(duration)
+        .with_progress(config, blocks)
 }
 
 /// Read a large file in reverse-order
-pub fn read_reversed(size: u64, block_size: usize, run: u32) -> Duration {
-    let path = format!("/scratch/read_reversed_{}_{}_{}.txt", size, block_size, run);
-    let mut file = BufWriter::new(File::create(&path).unwrap());
-    let mut prng = xorshift64(42);
+pub fn read_reversed(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, reuse, .. } = *config;
+    let path = format!("{}/read_reversed_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
+    let mut prng = Prng::new(config.prng, seed);
     let mut buffer = vec![0u8; block_size];
 
     // first create/fill the file
     for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
         for (j, x) in
             (&mut prng)
-                .take(usize::try_from(
-                    min(i+u64::try_from(block_size).unwrap(), size) - i
-                ).unwrap())
+                .take(step_size)
                 .enumerate()
         {
             buffer[j] = x as u8;
         }
+        mark(config, &mut buffer);
 
-        file.write_all(&buffer).unwrap();
+        with_retry(retries, || file.write_all(&buffer[..step_size])).unwrap();
     }
 
     mem::drop(file);
-    let mut file = File::open(&path).unwrap();
+    let mut file = with_retry(retries, || File::open(&path)).unwrap();
 
     // Now measure reads
+    let mut blocks = 0u64;
+    let mut paused = Duration::ZERO;
     let stopwatch = Instant::now();
 
-    // this division is a workaround for Range<u64> limitations
+    // this division is a workaround for Range<u64> limitations, rounded
+    // up so a trailing partial block is included
     for i in
-        (0..size/u64::try_from(block_size).unwrap())
+        (0..size.div_ceil(u64::try_from(block_size).unwrap()))
             .rev()
             .map(|i| i*u64::try_from(block_size).unwrap())
     {
+        if interrupted() || config.capped(blocks) {
+            break;
+        }
+        paused += think(config);
+
         let step_size = usize::try_from(
             min(i+u64::try_from(block_size).unwrap(), size) - i
         ).unwrap();
         
         hint::black_box({
-            file.seek(SeekFrom::Start(i)).unwrap();
+            for _ in 0..reuse {
+                file.seek(SeekFrom::Start(i)).unwrap();
 
-            file.read_exact(hint::black_box(&mut buffer[..step_size])).unwrap();
+                read_block(retries, &mut file, hint::black_box(&mut buffer[..step_size]), i/u64::try_from(block_size).unwrap(), i);
+            }
             &buffer
         });
+        blocks += 1;
     }
 
-    let duration = stopwatch.elapsed();
+    let duration = stopwatch.elapsed() - paused;
 
     mem::drop(file);
-    let file = File::create(&path).unwrap();
+    let file = with_retry(retries, || File::create(&path)).unwrap();
 
     // Truncate the file! Otherwise Veracruz may try to copy it back over
     // into the user's fs, which is a waste of (significant) time...
     //
     file.set_len(0).unwrap();
 
-    duration
+    Report::new(duration)
+        .with_progress(config, blocks)
+        .with("reuse", reuse)
+        .with("bytes_per_sec",
+            (blocks*u64::from(reuse)*u64::try_from(block_size).unwrap()) as f64
+                / duration.as_secs_f64()
+        )
 }
 
 /// Write a large file in reverse-order
-pub fn write_random(size: u64, block_size: usize, run: u32) -> Duration {
-    let path = format!("/scratch/write_random_{}_{}_{}.txt", size, block_size, run);
-    let mut file = File::create(&path).unwrap();
-    let prng = RefCell::new(xorshift64(42));
+pub fn write_random(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/write_random_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = with_retry(retries, || File::create(&path)).unwrap();
+    let prng = RefCell::new(Prng::new(config.prng, seed));
     let mut buffer = vec![0u8; block_size];
 
+    let mut timings = BlockTimings::new(config, size/u64::try_from(block_size).unwrap());
+    let mut sequence = Sequence::new(config, size/u64::try_from(block_size).unwrap());
+    let mut coverage = Coverage::new(size/u64::try_from(block_size).unwrap());
+    let mut blocks = 0u64;
+    let mut paused = Duration::ZERO;
     let stopwatch = Instant::now();
 
     // this may not touch every block, but that's ok
@@ -382,69 +838,94 @@ pub fn write_random(size: u64, block_size: usize, run: u32) -> Duration {
             .map(|_| prng.borrow_mut().next().unwrap() % count)
             .map(|i| i*u64::try_from(block_size).unwrap())
     {
+        if interrupted() || config.capped(blocks) {
+            break;
+        }
+        paused += think(config);
+
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
         for (j, x) in
             prng
                 .borrow_mut()
                 .deref_mut()
-                .take(usize::try_from(
-                    min(i+u64::try_from(block_size).unwrap(), size) - i
-                ).unwrap())
+                .take(step_size)
                 .enumerate()
         {
             buffer[j] = x as u8;
         }
+        mark(config, &mut buffer);
+        coverage.touch(i/u64::try_from(block_size).unwrap());
+        sequence.record(i/u64::try_from(block_size).unwrap(), Op::Write);
 
         
-        hint::black_box({
+        timings.time(i, || hint::black_box({
             file.seek(SeekFrom::Start(i)).unwrap();
 
-            let input = hint::black_box(&buffer);
-            file.write_all(input).unwrap();
-        });
+            let input = hint::black_box(&buffer[..step_size]);
+            with_retry(retries, || file.write_all(input)).unwrap();
+        }));
+        blocks += 1;
     }
 
     hint::black_box({
         file.flush().unwrap();
+        supported!(config.sync_mode.sync(&file, retries), file.set_len(0).unwrap());
     });
 
-    let duration = stopwatch.elapsed();
+    let duration = stopwatch.elapsed() - paused;
+    timings.write_csv(config);
+    sequence.write(config);
+
+    let metadata = file.metadata().unwrap();
 
     // Truncate the file! Otherwise Veracruz may try to copy it back over
     // into the user's fs, which is a waste of (significant) time...
     //
     file.set_len(0).unwrap();
 
-    duration
+    Report::new(duration)
+        .with_progress(config, blocks)
+        .with_coverage(&coverage)
+        .with_footprint(&metadata)
 }
 
 /// Update a large file in reverse-order
-pub fn update_random(size: u64, block_size: usize, run: u32) -> Duration {
-    let path = format!("/scratch/update_random_{}_{}_{}.txt", size, block_size, run);
-    let mut file = BufWriter::new(File::create(&path).unwrap());
-    let prng = RefCell::new(xorshift64(42));
+pub fn update_random(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/update_random_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
+    let prng = RefCell::new(Prng::new(config.prng, seed));
     let mut buffer = vec![0u8; block_size];
 
     // first create/fill the file
     for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
         for (j, x) in
             prng
                 .borrow_mut()
                 .deref_mut()
-                .take(usize::try_from(
-                    min(i+u64::try_from(block_size).unwrap(), size) - i
-                ).unwrap())
+                .take(step_size)
                 .enumerate()
         {
             buffer[j] = x as u8;
         }
+        mark(config, &mut buffer);
 
-        file.write_all(&buffer).unwrap();
+        with_retry(retries, || file.write_all(&buffer[..step_size])).unwrap();
     }
 
     mem::drop(file);
-    let mut file = File::create(&path).unwrap();
+    let mut file = with_retry(retries, || File::create(&path)).unwrap();
 
     // now measure updates
+    let mut sequence = Sequence::new(config, size/u64::try_from(block_size).unwrap());
+    let mut coverage = Coverage::new(size/u64::try_from(block_size).unwrap());
+    let mut blocks = 0u64;
+    let mut paused = Duration::ZERO;
     let stopwatch = Instant::now();
 
     // this may not touch every block, but that's ok
@@ -454,97 +935,2311 @@ pub fn update_random(size: u64, block_size: usize, run: u32) -> Duration {
             .map(|_| prng.borrow_mut().next().unwrap() % count)
             .map(|i| i*u64::try_from(block_size).unwrap())
     {
+        if interrupted() || config.capped(blocks) {
+            break;
+        }
+        paused += think(config);
+
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
         for (j, x) in
             prng
                 .borrow_mut()
                 .deref_mut()
-                .take(usize::try_from(
-                    min(i+u64::try_from(block_size).unwrap(), size) - i
-                ).unwrap())
+                .take(step_size)
                 .enumerate()
         {
             buffer[j] = x as u8;
         }
+        mark(config, &mut buffer);
+        coverage.touch(i/u64::try_from(block_size).unwrap());
+        sequence.record(i/u64::try_from(block_size).unwrap(), Op::Write);
 
         
         hint::black_box({
             file.seek(SeekFrom::Start(i)).unwrap();
 
-            let input = hint::black_box(&buffer);
-            file.write_all(input).unwrap();
+            let input = hint::black_box(&buffer[..step_size]);
+            with_retry(retries, || file.write_all(input)).unwrap();
         });
+        blocks += 1;
     }
 
     hint::black_box({
         file.flush().unwrap();
+        supported!(config.sync_mode.sync(&file, retries), file.set_len(0).unwrap());
     });
 
-    let duration = stopwatch.elapsed();
+    let duration = stopwatch.elapsed() - paused;
+    sequence.write(config);
 
     // Truncate the file! Otherwise Veracruz may try to copy it back over
     // into the user's fs, which is a waste of (significant) time...
     //
     file.set_len(0).unwrap();
 
-    duration
+    Report::new(duration)
+        .with_progress(config, blocks)
+        .with_coverage(&coverage)
 }
 
 /// Read a large file in reverse-order
-pub fn read_random(size: u64, block_size: usize, run: u32) -> Duration {
-    let path = format!("/scratch/read_random_{}_{}_{}.txt", size, block_size, run);
-    let mut file = BufWriter::new(File::create(&path).unwrap());
-    let mut prng = xorshift64(42);
+pub fn read_random(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, warmup, reuse, .. } = *config;
+    let path = format!("{}/read_random_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
+    let mut prng = Prng::new(config.prng, seed);
     let mut buffer = vec![0u8; block_size];
 
     // first create/fill the file
     for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
         for (j, x) in
             (&mut prng)
-                .take(usize::try_from(
-                    min(i+u64::try_from(block_size).unwrap(), size) - i
-                ).unwrap())
+                .take(step_size)
                 .enumerate()
         {
             buffer[j] = x as u8;
         }
+        mark(config, &mut buffer);
 
-        file.write_all(&buffer).unwrap();
+        with_retry(retries, || file.write_all(&buffer[..step_size])).unwrap();
     }
 
     mem::drop(file);
-    let mut file = File::open(&path).unwrap();
-
-    // Now measure reads
-    let stopwatch = Instant::now();
-
-    // this may not touch every block, but that's ok
-    let count = size/u64::try_from(block_size).unwrap();
-    for i in 
-        (0..count)
-            .map(|_| (&mut prng).next().unwrap() % count)
-            .map(|i| i*u64::try_from(block_size).unwrap())
-    {
-        let step_size = usize::try_from(
-            min(i+u64::try_from(block_size).unwrap(), size) - i
-        ).unwrap();
-        
-        hint::black_box({
-            file.seek(SeekFrom::Start(i)).unwrap();
+    let mut file = with_retry(retries, || File::open(&path)).unwrap();
 
-            file.read_exact(hint::black_box(&mut buffer[..step_size])).unwrap();
-            &buffer
-        });
+    // optionally read everything once first, so we measure warm reads
+    if warmup {
+        warm_up(&mut file, &mut buffer, retries);
     }
 
-    let duration = stopwatch.elapsed();
+    // Now measure reads
+    let mut sequence = Sequence::new(config, size/u64::try_from(block_size).unwrap());
+    let mut coverage = Coverage::new(size/u64::try_from(block_size).unwrap());
+    let (duration, blocks) = measure_read_random(
+        config, &mut file, &mut prng, &mut buffer, &mut sequence, &mut coverage
+    );
+    sequence.write(config);
 
     mem::drop(file);
-    let file = File::create(&path).unwrap();
+    let file = with_retry(retries, || File::create(&path)).unwrap();
 
     // Truncate the file! Otherwise Veracruz may try to copy it back over
     // into the user's fs, which is a waste of (significant) time...
     //
     file.set_len(0).unwrap();
 
-    duration
+    Report::new(duration)
+        .with_progress(config, blocks)
+        .with_coverage(&coverage)
+        .with("reuse", reuse)
+        .with("bytes_per_sec",
+            (blocks*u64::from(reuse)*u64::try_from(block_size).unwrap()) as f64
+                / duration.as_secs_f64()
+        )
+        .with("warmup", warmup)
+}
+
+/// Block indices 0..count in a random order, by a Fisher-Yates shuffle
+fn shuffled(count: u64, prng: &mut impl Iterator<Item=u64>) -> Vec<u64> {
+    let mut order = (0..count).collect::<Vec<_>>();
+    for i in (1..order.len()).rev() {
+        let j = usize::try_from(prng.next().unwrap() % u64::try_from(i+1).unwrap()).unwrap();
+        order.swap(i, j);
+    }
+    order
+}
+
+/// Read a large file --epochs times, each pass visiting every block once in
+/// the same shuffled order
+///
+/// This is epoch shuffling when loading a dataset, random like read_random
+/// but with one permutation fixed across passes. Every block is read each
+/// pass, and each epoch's runtime is reported so warm passes can be told
+/// from the first.
+pub fn read_shuffled(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, epochs, .. } = *config;
+    let path = format!("{}/read_shuffled_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
+    let mut prng = Prng::new(config.prng, seed);
+    let mut buffer = vec![0u8; block_size];
+
+    // first create/fill the file
+    for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
+        for (j, x) in
+            (&mut prng)
+                .take(step_size)
+                .enumerate()
+        {
+            buffer[j] = x as u8;
+        }
+        mark(config, &mut buffer);
+
+        with_retry(retries, || file.write_all(&buffer[..step_size])).unwrap();
+    }
+
+    mem::drop(file);
+    let mut file = with_retry(retries, || File::open(&path)).unwrap();
+
+    // the order is picked once, untimed
+    let order = shuffled(size.div_ceil(u64::try_from(block_size).unwrap()), &mut prng);
+
+    // Now measure reads
+    let mut epoch_durations = Vec::new();
+    let mut blocks = 0u64;
+    let mut paused = Duration::ZERO;
+    let stopwatch = Instant::now();
+
+    'epochs: for _ in 0..epochs {
+        let epoch_stopwatch = Instant::now();
+        let epoch_paused = paused;
+
+        for &index in &order {
+            if interrupted() || config.capped(blocks) {
+                break 'epochs;
+            }
+            paused += think(config);
+
+            let i = index*u64::try_from(block_size).unwrap();
+            let step_size = usize::try_from(
+                min(i+u64::try_from(block_size).unwrap(), size) - i
+            ).unwrap();
+
+            hint::black_box({
+                file.seek(SeekFrom::Start(i)).unwrap();
+
+                read_block(retries, &mut file, hint::black_box(&mut buffer[..step_size]), index, i);
+                &buffer
+            });
+            blocks += 1;
+        }
+
+        epoch_durations.push((epoch_stopwatch.elapsed() - (paused - epoch_paused)).as_secs_f64());
+    }
+
+    let duration = stopwatch.elapsed() - paused;
+
+    mem::drop(file);
+    let file = with_retry(retries, || File::create(&path)).unwrap();
+
+    // Truncate the file! Otherwise Veracruz may try to copy it back over
+    // into the user's fs, which is a waste of (significant) time...
+    //
+    file.set_len(0).unwrap();
+
+    Report::new(duration)
+        .with_progress(config, blocks)
+        .with("epochs", epochs)
+        .with("epoch_runtimes", epoch_durations)
+        .with("bytes_per_sec",
+            (blocks*u64::try_from(block_size).unwrap()) as f64 / duration.as_secs_f64()
+        )
+}
+
+/// Update a large file --epochs times, each pass visiting every block once
+/// in the same shuffled order
+///
+/// The write-side counterpart of read_shuffled, each epoch's runtime is
+/// reported alongside the total.
+pub fn update_shuffled(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, epochs, .. } = *config;
+    let path = format!("{}/update_shuffled_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
+    let mut prng = Prng::new(config.prng, seed);
+    let mut buffer = vec![0u8; block_size];
+
+    // first create/fill the file
+    for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
+        for (j, x) in
+            (&mut prng)
+                .take(step_size)
+                .enumerate()
+        {
+            buffer[j] = x as u8;
+        }
+        mark(config, &mut buffer);
+
+        with_retry(retries, || file.write_all(&buffer[..step_size])).unwrap();
+    }
+
+    mem::drop(file);
+    let mut file = with_retry(retries, || {
+        OpenOptions::new()
+            .write(true)
+            .open(&path)
+    }).unwrap();
+
+    // the order is picked once, untimed
+    let order = shuffled(size.div_ceil(u64::try_from(block_size).unwrap()), &mut prng);
+
+    // now measure updates
+    let mut epoch_durations = Vec::new();
+    let mut blocks = 0u64;
+    let mut paused = Duration::ZERO;
+    let stopwatch = Instant::now();
+
+    'epochs: for _ in 0..epochs {
+        let epoch_stopwatch = Instant::now();
+        let epoch_paused = paused;
+
+        for &index in &order {
+            if interrupted() || config.capped(blocks) {
+                break 'epochs;
+            }
+            paused += think(config);
+
+            let i = index*u64::try_from(block_size).unwrap();
+            let step_size = usize::try_from(
+                min(i+u64::try_from(block_size).unwrap(), size) - i
+            ).unwrap();
+            for (j, x) in
+                (&mut prng)
+                    .take(step_size)
+                    .enumerate()
+            {
+                buffer[j] = x as u8;
+            }
+            mark(config, &mut buffer);
+
+            hint::black_box({
+                file.seek(SeekFrom::Start(i)).unwrap();
+
+                let input = hint::black_box(&buffer[..step_size]);
+                with_retry(retries, || file.write_all(input)).unwrap();
+            });
+            blocks += 1;
+        }
+
+        epoch_durations.push((epoch_stopwatch.elapsed() - (paused - epoch_paused)).as_secs_f64());
+    }
+
+    hint::black_box({
+        file.flush().unwrap();
+        supported!(config.sync_mode.sync(&file, retries), file.set_len(0).unwrap());
+    });
+
+    let duration = stopwatch.elapsed() - paused;
+
+    // Truncate the file! Otherwise Veracruz may try to copy it back over
+    // into the user's fs, which is a waste of (significant) time...
+    //
+    file.set_len(0).unwrap();
+
+    Report::new(duration)
+        .with_progress(config, blocks)
+        .with("epochs", epochs)
+        .with("epoch_runtimes", epoch_durations)
+        .with("bytes_per_sec",
+            (blocks*u64::try_from(block_size).unwrap()) as f64 / duration.as_secs_f64()
+        )
+}
+
+/// Replay the block reads and writes loaded from --trace against a large
+/// file
+///
+/// This turns captured access patterns, such as one dumped with
+/// --dump-sequence, into a benchmark of their own. The file is filled
+/// first, untimed, so every read in the trace finds data.
+pub fn replay(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/replay_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
+    let mut prng = Prng::new(config.prng, seed);
+    let mut buffer = vec![0u8; block_size];
+    let trace = config.trace.as_ref().unwrap();
+
+    // first create/fill the file
+    for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
+        for (j, x) in
+            (&mut prng)
+                .take(step_size)
+                .enumerate()
+        {
+            buffer[j] = x as u8;
+        }
+        mark(config, &mut buffer);
+
+        with_retry(retries, || file.write_all(&buffer[..step_size])).unwrap();
+    }
+
+    mem::drop(file);
+    let mut file = with_retry(retries, || {
+        OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&path)
+    }).unwrap();
+
+    // then replay the trace
+    let mut blocks = 0u64;
+    let mut reads = 0u64;
+    let mut writes = 0u64;
+    let mut bytes = 0u64;
+    let mut paused = Duration::ZERO;
+    let stopwatch = Instant::now();
+
+    for &(index, op) in trace {
+        if interrupted() || config.capped(blocks) {
+            break;
+        }
+        paused += think(config);
+
+        let i = index*u64::try_from(block_size).unwrap();
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
+
+        match op {
+            Op::Read => {
+                hint::black_box({
+                    file.seek(SeekFrom::Start(i)).unwrap();
+
+                    read_block(retries, &mut file, hint::black_box(&mut buffer[..step_size]), index, i);
+                    &buffer
+                });
+                reads += 1;
+            }
+            Op::Write => {
+                for (j, x) in
+                    (&mut prng)
+                        .take(step_size)
+                        .enumerate()
+                {
+                    buffer[j] = x as u8;
+                }
+                mark(config, &mut buffer);
+
+                hint::black_box({
+                    file.seek(SeekFrom::Start(i)).unwrap();
+
+                    let input = hint::black_box(&buffer[..step_size]);
+                    with_retry(retries, || file.write_all(input)).unwrap();
+                });
+                writes += 1;
+            }
+        }
+        bytes += u64::try_from(step_size).unwrap();
+        blocks += 1;
+    }
+
+    hint::black_box({
+        file.flush().unwrap();
+    });
+
+    let duration = stopwatch.elapsed() - paused;
+
+    // Truncate the file! Otherwise Veracruz may try to copy it back over
+    // into the user's fs, which is a waste of (significant) time...
+    //
+    file.set_len(0).unwrap();
+
+    Report::new(duration)
+        .with_progress(config, blocks)
+        .with("ops", blocks)
+        .with("reads", reads)
+        .with("writes", writes)
+        .with("bytes_per_sec", bytes as f64 / duration.as_secs_f64())
+}
+
+/// Read random blocks of a large file, seeking relative to its end, then
+/// the same blocks again seeking to absolute offsets
+///
+/// Formats with trailers locate data this way. A VFS that re-stats the file
+/// to resolve each End seek will be slower than the absolute pass. Both
+/// kinds of seek are then checked, untimed, to land on the same data, and
+/// any blocks that differ are counted.
+pub fn read_from_end(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/read_from_end_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
+    let mut prng = Prng::new(config.prng, seed);
+    let mut buffer = vec![0u8; block_size];
+
+    // first create/fill the file
+    for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
+        for (j, x) in
+            (&mut prng)
+                .take(step_size)
+                .enumerate()
+        {
+            buffer[j] = x as u8;
+        }
+        mark(config, &mut buffer);
+
+        with_retry(retries, || file.write_all(&buffer[..step_size])).unwrap();
+    }
+
+    mem::drop(file);
+    let mut file = with_retry(retries, || File::open(&path)).unwrap();
+
+    // only whole blocks are read, so every read stays within the file
+    let count = size/u64::try_from(block_size).unwrap();
+    let offsets = (0..count)
+        .map(|_| prng.next().unwrap() % count)
+        .map(|i| i*u64::try_from(block_size).unwrap())
+        .collect::<Vec<_>>();
+
+    // Now measure end-relative reads
+    let stopwatch = Instant::now();
+
+    for &i in &offsets {
+        hint::black_box({
+            let from_end = -i64::try_from(size - i).unwrap();
+            file.seek(SeekFrom::End(hint::black_box(from_end))).unwrap();
+
+            with_retry(retries, || file.read_exact(hint::black_box(&mut buffer))).unwrap();
+            &buffer
+        });
+    }
+
+    let duration = stopwatch.elapsed();
+
+    // and absolute reads of the same blocks
+    let stopwatch = Instant::now();
+
+    for &i in &offsets {
+        hint::black_box({
+            file.seek(SeekFrom::Start(hint::black_box(i))).unwrap();
+
+            with_retry(retries, || file.read_exact(hint::black_box(&mut buffer))).unwrap();
+            &buffer
+        });
+    }
+
+    let absolute_duration = stopwatch.elapsed();
+
+    // then check, untimed, that both find the same blocks
+    let mut expected = vec![0u8; block_size];
+    let mut mismatches = 0u64;
+    for &i in &offsets {
+        file.seek(SeekFrom::End(-i64::try_from(size - i).unwrap())).unwrap();
+        with_retry(retries, || file.read_exact(&mut buffer)).unwrap();
+        file.seek(SeekFrom::Start(i)).unwrap();
+        with_retry(retries, || file.read_exact(&mut expected)).unwrap();
+        if buffer != expected {
+            mismatches += 1;
+        }
+    }
+
+    mem::drop(file);
+    let file = with_retry(retries, || File::create(&path)).unwrap();
+
+    // Truncate the file! Otherwise Veracruz may try to copy it back over
+    // into the user's fs, which is a waste of (significant) time...
+    //
+    file.set_len(0).unwrap();
+
+    Report::new(duration)
+        .with("reads", count)
+        .with("absolute_runtime", absolute_duration.as_secs_f64())
+        .with("end_vs_absolute_ratio",
+            duration.as_secs_f64() / absolute_duration.as_secs_f64()
+        )
+        .with("mismatches", mismatches)
+}
+
+/// Seek to a random block in a large file, then read the following
+/// --scan-blocks blocks sequentially
+///
+/// This models an index lookup followed by a range read, sitting between
+/// read_random and read_inorder. Scans are picked so they stay within the
+/// file, and there are enough of them to read about a file's worth of
+/// blocks.
+pub fn seek_then_scan(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, scan_blocks, .. } = *config;
+    let count = size/u64::try_from(block_size).unwrap();
+
+    let path = format!("{}/seek_then_scan_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
+    let mut prng = Prng::new(config.prng, seed);
+    let mut buffer = vec![0u8; block_size];
+
+    // first create/fill the file
+    for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
+        for (j, x) in
+            (&mut prng)
+                .take(step_size)
+                .enumerate()
+        {
+            buffer[j] = x as u8;
+        }
+        mark(config, &mut buffer);
+
+        with_retry(retries, || file.write_all(&buffer[..step_size])).unwrap();
+    }
+
+    mem::drop(file);
+    let mut file = with_retry(retries, || File::open(&path)).unwrap();
+
+    // Now measure seeks and scans
+    let scans = count/scan_blocks;
+    let stopwatch = Instant::now();
+
+    for i in
+        (0..scans)
+            .map(|_| prng.next().unwrap() % (count - scan_blocks + 1))
+            .map(|i| i*u64::try_from(block_size).unwrap())
+    {
+        hint::black_box({
+            file.seek(SeekFrom::Start(i)).unwrap();
+
+            for _ in 0..scan_blocks {
+                with_retry(retries, || file.read_exact(hint::black_box(&mut buffer))).unwrap();
+            }
+            &buffer
+        });
+    }
+
+    let duration = stopwatch.elapsed();
+
+    mem::drop(file);
+    let file = with_retry(retries, || File::create(&path)).unwrap();
+
+    // Truncate the file! Otherwise Veracruz may try to copy it back over
+    // into the user's fs, which is a waste of (significant) time...
+    //
+    file.set_len(0).unwrap();
+
+    let blocks_read = scans*scan_blocks;
+    Report::new(duration)
+        .with("scan_blocks", scan_blocks)
+        .with("scans", scans)
+        .with("blocks_read", blocks_read)
+        .with("bytes_per_sec",
+            (blocks_read*u64::try_from(block_size).unwrap()) as f64
+                / duration.as_secs_f64()
+        )
+}
+
+/// Largest buffer write_oneshot is willing to allocate, beyond this we
+/// would mostly be measuring the allocator, if we don't run out of memory
+pub const ONESHOT_LIMIT: u64 = 1024*1024*1024;
+
+/// Write a large file with a single write_all, compared against writing the
+/// same data block-by-block
+pub fn write_oneshot(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/write_oneshot_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut prng = Prng::new(config.prng, seed);
+    let mut buffer = Vec::new();
+    // this can still fail below the limit, so report it rather than abort
+    if let Err(err) = buffer.try_reserve_exact(usize::try_from(size).unwrap()) {
+        return Report::failed(&err);
+    }
+    buffer.extend(
+        (&mut prng)
+            .take(usize::try_from(size).unwrap())
+            .map(|x| x as u8)
+    );
+    mark(config, &mut buffer);
+
+    // first measure a single write
+    let mut file = with_retry(retries, || File::create(&path)).unwrap();
+    let stopwatch = Instant::now();
+
+    hint::black_box({
+        let input = hint::black_box(&buffer);
+        with_retry(retries, || file.write_all(input)).unwrap();
+    });
+
+    hint::black_box({
+        file.flush().unwrap();
+    });
+
+    let oneshot_duration = stopwatch.elapsed();
+
+    mem::drop(file);
+    let mut file = with_retry(retries, || File::create(&path)).unwrap();
+
+    // then the same data written block-by-block
+    let stopwatch = Instant::now();
+
+    for chunk in buffer.chunks(block_size) {
+        hint::black_box({
+            let input = hint::black_box(chunk);
+            with_retry(retries, || file.write_all(input)).unwrap();
+        });
+    }
+
+    hint::black_box({
+        file.flush().unwrap();
+    });
+
+    let block_duration = stopwatch.elapsed();
+
+    let metadata = file.metadata().unwrap();
+
+    // Truncate the file! Otherwise Veracruz may try to copy it back over
+    // into the user's fs, which is a waste of (significant) time...
+    //
+    file.set_len(0).unwrap();
+
+    Report::new(oneshot_duration)
+        .with_footprint(&metadata)
+        .with("oneshot_runtime", oneshot_duration.as_secs_f64())
+        .with("block_runtime", block_duration.as_secs_f64())
+}
+
+/// Write a large file in-order, calling sync_data after every block, compared
+/// against the same writes without syncing
+///
+/// This is what write-ahead logs do, the difference between the two gives
+/// the cost of a single sync.
+pub fn write_sync_data(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/write_sync_data_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut prng = Prng::new(config.prng, seed);
+    let mut buffer = vec![0u8; block_size];
+
+    // check we can sync at all before measuring anything
+    let file = with_retry(retries, || File::create(&path)).unwrap();
+    supported!(with_retry(retries, || file.sync_data()));
+    mem::drop(file);
+
+    let mut measure = |sync: bool| {
+        let mut file = with_retry(retries, || File::create(&path)).unwrap();
+        let stopwatch = Instant::now();
+
+        for i in (0..size).step_by(block_size) {
+            let step_size = usize::try_from(
+                min(i+u64::try_from(block_size).unwrap(), size) - i
+            ).unwrap();
+            for (j, x) in
+                (&mut prng)
+                    .take(step_size)
+                    .enumerate()
+            {
+                buffer[j] = x as u8;
+            }
+            mark(config, &mut buffer);
+
+            hint::black_box({
+                let input = hint::black_box(&buffer[..step_size]);
+                with_retry(retries, || file.write_all(input)).unwrap();
+
+                if sync {
+                    with_retry(retries, || file.sync_data()).unwrap();
+                }
+            });
+        }
+
+        hint::black_box({
+            file.flush().unwrap();
+        });
+
+        let duration = stopwatch.elapsed();
+
+        // Truncate the file! Otherwise Veracruz may try to copy it back over
+        // into the user's fs, which is a waste of (significant) time...
+        //
+        file.set_len(0).unwrap();
+
+        duration
+    };
+
+    // first measure writes with a sync after each block, then without
+    let sync_duration = measure(true);
+    let nosync_duration = measure(false);
+
+    let count = size.div_ceil(u64::try_from(block_size).unwrap());
+    let per_sync = sync_duration.saturating_sub(nosync_duration).as_secs_f64()
+        / count as f64;
+
+    Report::new(sync_duration)
+        .with("syncs", count)
+        .with("nosync_runtime", nosync_duration.as_secs_f64())
+        .with("per_sync", per_sync)
+}
+
+/// Append each block of a large file through a fresh handle, sync_all it,
+/// close it, then reopen the file and read the block back, timing every
+/// cycle as a whole
+///
+/// This is the pessimistic round trip a write-ahead log pays to know a
+/// record is durable. Blocks read back are compared against what was
+/// written, counting any mismatches.
+pub fn durable_roundtrip(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/durable_roundtrip_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut prng = Prng::new(config.prng, seed);
+    let mut buffer = vec![0u8; block_size];
+    let mut readback = vec![0u8; block_size];
+
+    // check we can sync at all before measuring anything
+    let file = with_retry(retries, || File::create(&path)).unwrap();
+    supported!(with_retry(retries, || file.sync_all()));
+    mem::drop(file);
+
+    let mut latencies = Vec::new();
+    let mut mismatches = 0u64;
+    let mut blocks = 0u64;
+    let mut paused = Duration::ZERO;
+    let stopwatch = Instant::now();
+
+    for i in (0..size).step_by(block_size) {
+        if interrupted() || config.capped(blocks) {
+            break;
+        }
+        paused += think(config);
+
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
+        for (j, x) in
+            (&mut prng)
+                .take(step_size)
+                .enumerate()
+        {
+            buffer[j] = x as u8;
+        }
+        mark(config, &mut buffer);
+
+        let cycle = Instant::now();
+        hint::black_box({
+            let mut file = with_retry(retries, || {
+                OpenOptions::new()
+                    .append(true)
+                    .open(&path)
+            }).unwrap();
+            let input = hint::black_box(&buffer[..step_size]);
+            with_retry(retries, || file.write_all(input)).unwrap();
+            with_retry(retries, || file.sync_all()).unwrap();
+            mem::drop(file);
+
+            let mut file = with_retry(retries, || File::open(&path)).unwrap();
+            file.seek(SeekFrom::Start(i)).unwrap();
+            with_retry(retries, || file.read_exact(hint::black_box(&mut readback[..step_size]))).unwrap();
+            &readback
+        });
+        latencies.push(cycle.elapsed());
+
+        if buffer[..step_size] != readback[..step_size] {
+            mismatches += 1;
+        }
+        blocks += 1;
+    }
+
+    let duration = stopwatch.elapsed() - paused;
+
+    let file = with_retry(retries, || File::create(&path)).unwrap();
+
+    // Truncate the file! Otherwise Veracruz may try to copy it back over
+    // into the user's fs, which is a waste of (significant) time...
+    //
+    file.set_len(0).unwrap();
+
+    Report::new(duration)
+        .with_progress(config, blocks)
+        .with("mismatches", mismatches)
+        .with_latencies("roundtrip", &mut latencies)
+}
+
+/// Read past the end of a large file
+///
+/// A plain read past EOF should return zero bytes and read_exact should fail
+/// with UnexpectedEof, but this is up to the VFS, so we record what we
+/// actually observe along with the timing.
+pub fn read_past_eof(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/read_past_eof_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
+    let mut prng = Prng::new(config.prng, seed);
+    let mut buffer = vec![0u8; block_size];
+
+    // first create/fill the file
+    for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
+        for (j, x) in
+            (&mut prng)
+                .take(step_size)
+                .enumerate()
+        {
+            buffer[j] = x as u8;
+        }
+        mark(config, &mut buffer);
+
+        with_retry(retries, || file.write_all(&buffer[..step_size])).unwrap();
+    }
+
+    mem::drop(file);
+    let mut file = with_retry(retries, || File::open(&path)).unwrap();
+
+    // Now measure reads, each one block further past the end
+    let mut zero_reads = 0u64;
+    let mut nonzero_reads = 0u64;
+    let mut errors = 0u64;
+    let stopwatch = Instant::now();
+
+    for i in
+        (0..size/u64::try_from(block_size).unwrap())
+            .map(|i| size + i*u64::try_from(block_size).unwrap())
+    {
+        let res = hint::black_box({
+            file.seek(SeekFrom::Start(i)).unwrap();
+
+            file.read(hint::black_box(&mut buffer))
+        });
+
+        match res {
+            Ok(0) => zero_reads += 1,
+            Ok(_) => nonzero_reads += 1,
+            Err(_) => errors += 1,
+        }
+    }
+
+    let duration = stopwatch.elapsed();
+
+    // read_exact is expected to fail with UnexpectedEof here
+    file.seek(SeekFrom::Start(size)).unwrap();
+    let read_exact = match file.read_exact(&mut buffer) {
+        Ok(()) => "Ok".to_owned(),
+        Err(err) => format!("{:?}", err.kind()),
+    };
+
+    mem::drop(file);
+    let file = with_retry(retries, || File::create(&path)).unwrap();
+
+    // Truncate the file! Otherwise Veracruz may try to copy it back over
+    // into the user's fs, which is a waste of (significant) time...
+    //
+    file.set_len(0).unwrap();
+
+    Report::new(duration)
+        .with("eof_zero_reads", zero_reads)
+        .with("eof_nonzero_reads", nonzero_reads)
+        .with("eof_errors", errors)
+        .with("eof_read_exact", read_exact)
+}
+
+/// Read a large file just after truncating it to zero length
+///
+/// Our cleanup truncates files, this checks whether reads of a just-truncated
+/// file return EOF immediately, or make the VFS do work. As with
+/// read_past_eof we record what we observe along with the timing.
+pub fn read_after_truncate(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/read_after_truncate_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
+    let mut prng = Prng::new(config.prng, seed);
+    let mut buffer = vec![0u8; block_size];
+
+    // first create/fill the file
+    for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
+        for (j, x) in
+            (&mut prng)
+                .take(step_size)
+                .enumerate()
+        {
+            buffer[j] = x as u8;
+        }
+        mark(config, &mut buffer);
+
+        with_retry(retries, || file.write_all(&buffer[..step_size])).unwrap();
+    }
+
+    mem::drop(file);
+    let mut file = with_retry(retries, || {
+        OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&path)
+    }).unwrap();
+
+    // then truncate it
+    let stopwatch = Instant::now();
+    hint::black_box({
+        file.set_len(0).unwrap();
+    });
+    let truncate_duration = stopwatch.elapsed();
+
+    // Now measure reads, which should all hit EOF
+    let mut zero_reads = 0u64;
+    let mut nonzero_reads = 0u64;
+    let mut errors = 0u64;
+    let stopwatch = Instant::now();
+
+    let count = size/u64::try_from(block_size).unwrap();
+    for _ in 0..count {
+        let res = hint::black_box(
+            file.read(hint::black_box(&mut buffer))
+        );
+
+        match res {
+            Ok(0) => zero_reads += 1,
+            Ok(_) => nonzero_reads += 1,
+            Err(_) => errors += 1,
+        }
+    }
+
+    let duration = stopwatch.elapsed();
+
+    // the file is already truncated, so there's nothing left to clean up
+
+    Report::new(duration)
+        .with("truncate_runtime", truncate_duration.as_secs_f64())
+        .with("eof_zero_reads", zero_reads)
+        .with("eof_nonzero_reads", nonzero_reads)
+        .with("eof_errors", errors)
+}
+
+/// Fill a large file, then reopen it with truncate, timing only the open
+///
+/// Unlike the set_len(0) in read_after_truncate, this frees the file's
+/// blocks through the open path, which the VFS may handle differently.
+pub fn open_truncate(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/open_truncate_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
+    let mut prng = Prng::new(config.prng, seed);
+    let mut buffer = vec![0u8; block_size];
+
+    // first create/fill the file
+    for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
+        for (j, x) in
+            (&mut prng)
+                .take(step_size)
+                .enumerate()
+        {
+            buffer[j] = x as u8;
+        }
+        mark(config, &mut buffer);
+
+        with_retry(retries, || file.write_all(&buffer[..step_size])).unwrap();
+    }
+
+    file.flush().unwrap();
+    mem::drop(file);
+
+    // then measure reopening it with truncate
+    let stopwatch = Instant::now();
+
+    let file = hint::black_box({
+        let path = hint::black_box(&path);
+        with_retry(retries, || {
+            OpenOptions::new()
+                .write(true)
+                .truncate(true)
+                .open(path)
+        }).unwrap()
+    });
+
+    let duration = stopwatch.elapsed();
+
+    assert_eq!(file.metadata().unwrap().len(), 0);
+
+    // the file is already truncated, so there's nothing left to clean up
+
+    Report::new(duration)
+        .with("truncated_bytes", size)
+}
+
+/// Fill a large file and then shrink it to zero in block_size steps
+///
+/// Each truncation frees the same amount, so the per-step latencies show
+/// whether the cost depends on how large the file still is. The filled file
+/// is then truncated to zero in one go, which costs about one step if
+/// freeing blocks is cheap, or about all of them if it scales with the
+/// amount freed.
+pub fn truncate_shrink(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/truncate_shrink_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut prng = Prng::new(config.prng, seed);
+    let mut buffer = vec![0u8; block_size];
+
+    let mut fill = || {
+        let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
+        for i in (0..size).step_by(block_size) {
+            let step_size = usize::try_from(
+                min(i+u64::try_from(block_size).unwrap(), size) - i
+            ).unwrap();
+            for (j, x) in
+                (&mut prng)
+                    .take(step_size)
+                    .enumerate()
+            {
+                buffer[j] = x as u8;
+            }
+            mark(config, &mut buffer);
+
+            with_retry(retries, || file.write_all(&buffer[..step_size])).unwrap();
+        }
+        mem::drop(file);
+
+        with_retry(retries, || {
+            OpenOptions::new()
+                .write(true)
+                .open(&path)
+        }).unwrap()
+    };
+
+    // first create/fill the file
+    let file = fill();
+
+    // then measure shrinking it a block at a time
+    let count = size/u64::try_from(block_size).unwrap();
+    let mut timings = BlockTimings::new(config, count);
+    let mut latencies = Vec::with_capacity(usize::try_from(count).unwrap());
+    let mut blocks = 0u64;
+    let stopwatch = Instant::now();
+
+    // this division is a workaround for Range<u64> limitations, rounded
+    // up so a trailing partial block is included
+    for i in
+        (0..size.div_ceil(u64::try_from(block_size).unwrap()))
+            .rev()
+            .map(|i| i*u64::try_from(block_size).unwrap())
+    {
+        if interrupted() {
+            break;
+        }
+
+        let step = Instant::now();
+        timings.time(i, || {
+            hint::black_box({
+                with_retry(retries, || file.set_len(hint::black_box(i))).unwrap();
+            })
+        });
+        latencies.push(step.elapsed());
+        blocks += 1;
+    }
+
+    let duration = stopwatch.elapsed();
+    timings.write_csv(config);
+    mem::drop(file);
+
+    // and then truncating the whole file at once
+    let file = fill();
+
+    let stopwatch = Instant::now();
+    hint::black_box({
+        with_retry(retries, || file.set_len(0)).unwrap();
+    });
+    let whole_duration = stopwatch.elapsed();
+
+    // the file is already truncated, so there's nothing left to clean up
+
+    let mut report = Report::new(duration)
+        .with_progress(config, blocks)
+        .with("whole_truncate_runtime", whole_duration.as_secs_f64());
+    if !latencies.is_empty() {
+        let mean = duration.as_secs_f64() / latencies.len() as f64;
+        report = report.with("whole_vs_step_ratio", whole_duration.as_secs_f64() / mean);
+    }
+    report.with_latencies("truncate", &mut latencies)
+}
+
+/// Write a large file's worth of blocks, all to the same block
+///
+/// The same number of writes spread over distinct blocks is also measured for
+/// comparison, a large gap shows whether the VFS rewrites the block in place,
+/// or pays for copy-on-write/log-structured updates on every write.
+pub fn hotspot_write(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/hotspot_write_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = with_retry(retries, || {
+        OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)
+    }).unwrap();
+    let mut prng = Prng::new(config.prng, seed);
+    let mut buffer = vec![0u8; block_size];
+
+    let stopwatch = Instant::now();
+
+    let count = size/u64::try_from(block_size).unwrap();
+    for _ in 0..count {
+        for (j, x) in
+            (&mut prng)
+                .take(block_size)
+                .enumerate()
+        {
+            buffer[j] = x as u8;
+        }
+        mark(config, &mut buffer);
+
+        hint::black_box({
+            file.seek(SeekFrom::Start(0)).unwrap();
+
+            let input = hint::black_box(&buffer);
+            with_retry(retries, || file.write_all(input)).unwrap();
+        });
+    }
+
+    hint::black_box({
+        file.flush().unwrap();
+    });
+
+    let duration = stopwatch.elapsed();
+
+    // the block should now hold the last data we wrote
+    let mut last = vec![0u8; block_size];
+    file.seek(SeekFrom::Start(0)).unwrap();
+    let last_write_visible = count == 0 || (
+        with_retry(retries, || file.read_exact(&mut last)).is_ok()
+            && last == buffer
+    );
+
+    // then the same number of writes, but spread over distinct blocks
+    file.set_len(0).unwrap();
+    let stopwatch = Instant::now();
+
+    for i in
+        (0..count)
+            .map(|i| i*u64::try_from(block_size).unwrap())
+    {
+        for (j, x) in
+            (&mut prng)
+                .take(block_size)
+                .enumerate()
+        {
+            buffer[j] = x as u8;
+        }
+        mark(config, &mut buffer);
+
+        hint::black_box({
+            file.seek(SeekFrom::Start(i)).unwrap();
+
+            let input = hint::black_box(&buffer);
+            with_retry(retries, || file.write_all(input)).unwrap();
+        });
+    }
+
+    hint::black_box({
+        file.flush().unwrap();
+    });
+
+    let spread_duration = stopwatch.elapsed();
+
+    // Truncate the file! Otherwise Veracruz may try to copy it back over
+    // into the user's fs, which is a waste of (significant) time...
+    //
+    file.set_len(0).unwrap();
+
+    Report::new(duration)
+        .with("writes", count)
+        .with("last_write_visible", last_write_visible)
+        .with("spread_runtime", spread_duration.as_secs_f64())
+        .with("hotspot_ratio",
+            duration.as_secs_f64() / spread_duration.as_secs_f64()
+        )
+}
+
+/// Write a large file in-order, timing the first write separately
+///
+/// The first write after File::create may pay one-time allocation or
+/// capability costs, this separates them from the steady-state writes.
+pub fn write_first(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/write_first_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = with_retry(retries, || File::create(&path)).unwrap();
+    let mut prng = Prng::new(config.prng, seed);
+    let mut buffer = vec![0u8; block_size];
+
+    let mut first_duration = Duration::ZERO;
+    let stopwatch = Instant::now();
+
+    for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
+        for (j, x) in
+            (&mut prng)
+                .take(step_size)
+                .enumerate()
+        {
+            buffer[j] = x as u8;
+        }
+        mark(config, &mut buffer);
+
+        hint::black_box({
+            let input = hint::black_box(&buffer[..step_size]);
+            with_retry(retries, || file.write_all(input)).unwrap();
+        });
+
+        if i == 0 {
+            first_duration = stopwatch.elapsed();
+        }
+    }
+
+    hint::black_box({
+        file.flush().unwrap();
+    });
+
+    let duration = stopwatch.elapsed();
+    let steady_duration = duration - first_duration;
+    let steady_writes = size.saturating_sub(1) / u64::try_from(block_size).unwrap();
+
+    // Truncate the file! Otherwise Veracruz may try to copy it back over
+    // into the user's fs, which is a waste of (significant) time...
+    //
+    file.set_len(0).unwrap();
+
+    Report::new(duration)
+        .with("first_write", first_duration.as_secs_f64())
+        .with("steady_state", steady_duration.as_secs_f64())
+        .with("steady_state_writes", steady_writes)
+}
+
+/// Write a large file in-order with writes that double in size, from 512
+/// bytes up to block_size, which the rest of the file is then written in
+///
+/// Files are often written with small headers followed by a large payload,
+/// which the fixed-size modes can't model. The sizes used, and how many
+/// writes were made at each, are reported as the schedule.
+pub fn write_ramp(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/write_ramp_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = with_retry(retries, || File::create(&path)).unwrap();
+    let mut prng = Prng::new(config.prng, seed);
+    let mut buffer = vec![0u8; block_size];
+
+    let mut schedule: Vec<(usize, u64)> = Vec::new();
+    let mut step = min(RAMP_START, block_size);
+    let mut i = 0u64;
+    let stopwatch = Instant::now();
+
+    while i < size {
+        if interrupted() {
+            break;
+        }
+
+        let step_size = usize::try_from(
+            min(i+u64::try_from(step).unwrap(), size) - i
+        ).unwrap();
+        for (j, x) in
+            (&mut prng)
+                .take(step_size)
+                .enumerate()
+        {
+            buffer[j] = x as u8;
+        }
+        mark(config, &mut buffer[..step_size]);
+
+        hint::black_box({
+            let input = hint::black_box(&buffer[..step_size]);
+            with_retry(retries, || file.write_all(input)).unwrap();
+        });
+
+        match schedule.last_mut() {
+            Some((last, writes)) if *last == step => *writes += 1,
+            _ => schedule.push((step, 1)),
+        }
+        i += u64::try_from(step_size).unwrap();
+        step = min(2*step, block_size);
+    }
+
+    hint::black_box({
+        file.flush().unwrap();
+    });
+
+    let duration = stopwatch.elapsed();
+
+    let metadata = file.metadata().unwrap();
+
+    // Truncate the file! Otherwise Veracruz may try to copy it back over
+    // into the user's fs, which is a waste of (significant) time...
+    //
+    file.set_len(0).unwrap();
+
+    Report::new(duration)
+        .with_footprint(&metadata)
+        .with("bytes_per_sec", i as f64 / duration.as_secs_f64())
+        .with("schedule_block_sizes", schedule.iter()
+            .map(|&(step, _)| step)
+            .collect::<Vec<_>>())
+        .with("schedule_writes", schedule.iter()
+            .map(|&(_, writes)| writes)
+            .collect::<Vec<_>>())
+}
+
+/// Write blocks at random byte offsets in a large file
+///
+/// Unlike the other random modes the offsets are not multiples of
+/// block_size, so writes straddle block boundaries and may force the VFS to
+/// read-modify-write partial blocks.
+pub fn write_unaligned(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/write_unaligned_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
+    let prng = RefCell::new(Prng::new(config.prng, seed));
+    let mut buffer = vec![0u8; block_size];
+
+    // first create/fill the file
+    for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
+        for (j, x) in
+            prng
+                .borrow_mut()
+                .deref_mut()
+                .take(step_size)
+                .enumerate()
+        {
+            buffer[j] = x as u8;
+        }
+        mark(config, &mut buffer);
+
+        with_retry(retries, || file.write_all(&buffer[..step_size])).unwrap();
+    }
+
+    mem::drop(file);
+    let mut file = with_retry(retries, || {
+        OpenOptions::new()
+            .write(true)
+            .open(&path)
+    }).unwrap();
+
+    // now measure unaligned writes
+    let stopwatch = Instant::now();
+
+    // offsets are picked so every write stays within the file
+    let count = size/u64::try_from(block_size).unwrap();
+    let span = (size+1).saturating_sub(u64::try_from(block_size).unwrap());
+    for i in
+        (0..count)
+            .map(|_| prng.borrow_mut().next().unwrap() % span)
+    {
+        for (j, x) in
+            prng
+                .borrow_mut()
+                .deref_mut()
+                .take(block_size)
+                .enumerate()
+        {
+            buffer[j] = x as u8;
+        }
+        mark(config, &mut buffer);
+
+        hint::black_box({
+            file.seek(SeekFrom::Start(i)).unwrap();
+
+            let input = hint::black_box(&buffer);
+            with_retry(retries, || file.write_all(input)).unwrap();
+        });
+    }
+
+    hint::black_box({
+        file.flush().unwrap();
+    });
+
+    let duration = stopwatch.elapsed();
+
+    // every write stayed within the file, so it shouldn't have grown
+    assert_eq!(file.metadata().unwrap().len(), size);
+
+    // Truncate the file! Otherwise Veracruz may try to copy it back over
+    // into the user's fs, which is a waste of (significant) time...
+    //
+    file.set_len(0).unwrap();
+
+    Report::new(duration)
+}
+
+/// Write small records of --record-size bytes at random byte offsets in a
+/// large file
+///
+/// This models formats that update small records in place. There are
+/// size/record_size writes, and any that straddle a block_size boundary
+/// are counted, since those may force the VFS to read-modify-write two
+/// blocks rather than one.
+pub fn write_records(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, record_size, .. } = *config;
+    let path = format!("{}/write_records_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
+    let mut prng = Prng::new(config.prng, seed);
+    let mut buffer = vec![0u8; block_size];
+
+    // first create/fill the file
+    for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
+        for (j, x) in
+            (&mut prng)
+                .take(step_size)
+                .enumerate()
+        {
+            buffer[j] = x as u8;
+        }
+        mark(config, &mut buffer);
+
+        with_retry(retries, || file.write_all(&buffer[..step_size])).unwrap();
+    }
+
+    mem::drop(file);
+    let mut file = with_retry(retries, || {
+        OpenOptions::new()
+            .write(true)
+            .open(&path)
+    }).unwrap();
+
+    // now measure record writes
+    let mut record = vec![0u8; record_size];
+    let mut straddling = 0u64;
+    let stopwatch = Instant::now();
+
+    // offsets are picked so every write stays within the file
+    let count = size/u64::try_from(record_size).unwrap();
+    let span = size+1 - u64::try_from(record_size).unwrap();
+    for _ in 0..count {
+        let i = prng.next().unwrap() % span;
+        for (x, y) in record.iter_mut().zip(&mut prng) {
+            *x = y as u8;
+        }
+        mark(config, &mut record);
+
+        let end = i + u64::try_from(record_size).unwrap() - 1;
+        if i / u64::try_from(block_size).unwrap() != end / u64::try_from(block_size).unwrap() {
+            straddling += 1;
+        }
+
+        hint::black_box({
+            file.seek(SeekFrom::Start(i)).unwrap();
+
+            let input = hint::black_box(&record);
+            with_retry(retries, || file.write_all(input)).unwrap();
+        });
+    }
+
+    hint::black_box({
+        file.flush().unwrap();
+    });
+
+    let duration = stopwatch.elapsed();
+
+    // every write stayed within the file, so it shouldn't have grown
+    assert_eq!(file.metadata().unwrap().len(), size);
+
+    // Truncate the file! Otherwise Veracruz may try to copy it back over
+    // into the user's fs, which is a waste of (significant) time...
+    //
+    file.set_len(0).unwrap();
+
+    Report::new(duration)
+        .with("record_size", record_size)
+        .with("records", count)
+        .with("records_per_sec", count as f64 / duration.as_secs_f64())
+        .with("straddling_records", straddling)
+}
+
+/// Read blocks at random byte offsets in a large file
+///
+/// Unlike the other random modes the offsets are not multiples of
+/// block_size, so reads straddle block boundaries.
+pub fn read_unaligned(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/read_unaligned_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
+    let mut prng = Prng::new(config.prng, seed);
+    let mut buffer = vec![0u8; block_size];
+
+    // first create/fill the file
+    for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
+        for (j, x) in
+            (&mut prng)
+                .take(step_size)
+                .enumerate()
+        {
+            buffer[j] = x as u8;
+        }
+        mark(config, &mut buffer);
+
+        with_retry(retries, || file.write_all(&buffer[..step_size])).unwrap();
+    }
+
+    mem::drop(file);
+    let mut file = with_retry(retries, || File::open(&path)).unwrap();
+
+    // Now measure unaligned reads
+    let stopwatch = Instant::now();
+
+    // offsets are picked so every read stays within the file
+    let count = size/u64::try_from(block_size).unwrap();
+    let span = (size+1).saturating_sub(u64::try_from(block_size).unwrap());
+    for i in
+        (0..count)
+            .map(|_| prng.next().unwrap() % span)
+    {
+        hint::black_box({
+            file.seek(SeekFrom::Start(i)).unwrap();
+
+            with_retry(retries, || file.read_exact(hint::black_box(&mut buffer))).unwrap();
+            &buffer
+        });
+    }
+
+    let duration = stopwatch.elapsed();
+
+    mem::drop(file);
+    let file = with_retry(retries, || File::create(&path)).unwrap();
+
+    // Truncate the file! Otherwise Veracruz may try to copy it back over
+    // into the user's fs, which is a waste of (significant) time...
+    //
+    file.set_len(0).unwrap();
+
+    Report::new(duration)
+}
+
+/// Read the same large file in-order and then in random-order
+///
+/// Both reads use the same loops as read_inorder and read_random, but run
+/// back-to-back in one process on one file, so their ratio, the locality
+/// penalty, isn't skewed by variance between separate runs. The random
+/// reads come second, so with --warmup the file is warmed up before
+/// either to keep the comparison fair.
+pub fn locality_compare(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, warmup, .. } = *config;
+    let path = format!("{}/locality_compare_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
+    let mut prng = Prng::new(config.prng, seed);
+    let mut buffer = vec![0u8; block_size];
+
+    // first create/fill the file
+    for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
+        for (j, x) in
+            (&mut prng)
+                .take(step_size)
+                .enumerate()
+        {
+            buffer[j] = x as u8;
+        }
+        mark(config, &mut buffer);
+
+        with_retry(retries, || file.write_all(&buffer[..step_size])).unwrap();
+    }
+
+    mem::drop(file);
+    let mut file = with_retry(retries, || File::open(&path)).unwrap();
+
+    // optionally read everything once first, so we measure warm reads
+    if warmup {
+        warm_up(&mut file, &mut buffer, retries);
+        file.seek(SeekFrom::Start(0)).unwrap();
+    }
+
+    // Now measure both kinds of reads
+    let (inorder_duration, inorder_blocks) = measure_read_inorder(config, &mut file, &mut buffer);
+    let mut sequence = Sequence::new(config, size/u64::try_from(block_size).unwrap());
+    let mut coverage = Coverage::new(size/u64::try_from(block_size).unwrap());
+    let (random_duration, random_blocks) = measure_read_random(
+        config, &mut file, &mut prng, &mut buffer, &mut sequence, &mut coverage
+    );
+    sequence.write(config);
+
+    mem::drop(file);
+    let file = with_retry(retries, || File::create(&path)).unwrap();
+
+    // Truncate the file! Otherwise Veracruz may try to copy it back over
+    // into the user's fs, which is a waste of (significant) time...
+    //
+    file.set_len(0).unwrap();
+
+    Report::new(inorder_duration + random_duration)
+        .with_progress(config, inorder_blocks + random_blocks)
+        .with_coverage(&coverage)
+        .with("warmup", warmup)
+        .with("inorder_runtime", inorder_duration.as_secs_f64())
+        .with("random_runtime", random_duration.as_secs_f64())
+        .with("locality_penalty",
+            random_duration.as_secs_f64() / inorder_duration.as_secs_f64()
+        )
+}
+
+/// Read the same large file in-order, once opened read-only and once opened
+/// read-write
+///
+/// Open flags decide which capabilities a handle carries, so this checks
+/// whether holding write capabilities costs anything on every read, not
+/// just at open time. Both passes use the same loop as read_inorder.
+pub fn read_ro_vs_rw(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/read_ro_vs_rw_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
+    let mut prng = Prng::new(config.prng, seed);
+    let mut buffer = vec![0u8; block_size];
+
+    // first create/fill the file
+    for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
+        for (j, x) in
+            (&mut prng)
+                .take(step_size)
+                .enumerate()
+        {
+            buffer[j] = x as u8;
+        }
+        mark(config, &mut buffer);
+
+        with_retry(retries, || file.write_all(&buffer[..step_size])).unwrap();
+    }
+
+    mem::drop(file);
+
+    // Now measure reads through a read-only handle
+    let mut file = with_retry(retries, || {
+        OpenOptions::new()
+            .read(true)
+            .open(&path)
+    }).unwrap();
+    let (ro_duration, ro_blocks) = measure_read_inorder(config, &mut file, &mut buffer);
+    mem::drop(file);
+
+    // and through a read-write handle
+    let mut file = with_retry(retries, || {
+        OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&path)
+    }).unwrap();
+    let (rw_duration, rw_blocks) = measure_read_inorder(config, &mut file, &mut buffer);
+
+    // Truncate the file! Otherwise Veracruz may try to copy it back over
+    // into the user's fs, which is a waste of (significant) time...
+    //
+    file.set_len(0).unwrap();
+
+    Report::new(ro_duration + rw_duration)
+        .with_progress(config, ro_blocks + rw_blocks)
+        .with("ro_runtime", ro_duration.as_secs_f64())
+        .with("rw_runtime", rw_duration.as_secs_f64())
+        .with("rw_penalty",
+            rw_duration.as_secs_f64() / ro_duration.as_secs_f64()
+        )
+}
+
+/// Read a large file one byte at a time through Read::bytes, then in
+/// blocks with the same loop as read_inorder
+///
+/// bytes() on an unbuffered File makes a read call per byte, which is easy
+/// to write by accident. The block reads give the comparison showing how
+/// much that costs. The bytes are summed so none of them can be skipped.
+pub fn read_bytes_iter(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/read_bytes_iter_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
+    let mut prng = Prng::new(config.prng, seed);
+    let mut buffer = vec![0u8; block_size];
+
+    // first create/fill the file
+    for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
+        for (j, x) in
+            (&mut prng)
+                .take(step_size)
+                .enumerate()
+        {
+            buffer[j] = x as u8;
+        }
+        mark(config, &mut buffer);
+
+        with_retry(retries, || file.write_all(&buffer[..step_size])).unwrap();
+    }
+
+    mem::drop(file);
+
+    // Now measure reading byte by byte
+    let file = with_retry(retries, || File::open(&path)).unwrap();
+    let stopwatch = Instant::now();
+
+    let (consumed, sum) = hint::black_box({
+        let mut consumed = 0u64;
+        let mut sum = 0u64;
+        // the unbuffered bytes() is exactly what's being measured
+        #[allow(clippy::unbuffered_bytes)]
+        for byte in hint::black_box(file).bytes() {
+            sum = sum.wrapping_add(u64::from(byte.unwrap()));
+            consumed += 1;
+        }
+        (consumed, sum)
+    });
+
+    let duration = stopwatch.elapsed();
+
+    assert_eq!(consumed, size);
+
+    // and in blocks
+    let mut file = with_retry(retries, || File::open(&path)).unwrap();
+    let (block_duration, _) = measure_read_inorder(config, &mut file, &mut buffer);
+    mem::drop(file);
+    let file = with_retry(retries, || File::create(&path)).unwrap();
+
+    // Truncate the file! Otherwise Veracruz may try to copy it back over
+    // into the user's fs, which is a waste of (significant) time...
+    //
+    file.set_len(0).unwrap();
+
+    Report::new(duration)
+        .with("bytes_consumed", consumed)
+        .with("byte_sum", sum)
+        .with("bytes_per_sec", consumed as f64 / duration.as_secs_f64())
+        .with("block_read_runtime", block_duration.as_secs_f64())
+        .with("bytes_iter_slowdown",
+            duration.as_secs_f64() / block_duration.as_secs_f64()
+        )
+}
+
+/// Append a large file's worth of blocks from one thread while another
+/// follows behind, reading each block as soon as it appears
+///
+/// This models tailing a log. Every block is filled with a byte derived from
+/// its index, so the reader can tell a block it only saw part of, counted
+/// as a partial read, from one whose contents are wrong, counted as torn.
+/// Only whole blocks are appended, and only the reader is measured.
+pub fn tail_read(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, .. } = *config;
+    let path = format!("{}/tail_read_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut writer = with_retry(retries, || File::create(&path)).unwrap();
+    let mut reader = with_retry(retries, || File::open(&path)).unwrap();
+
+    // non-zero, so unwritten holes don't pass as data
+    let fill = |i: u64| (i % 255) as u8 + 1;
+
+    let count = size/u64::try_from(block_size).unwrap();
+    let done = AtomicBool::new(false);
+    let mut partial_reads = 0u64;
+    let mut torn = 0u64;
+    let mut eof_waits = 0u64;
+    let mut blocks = 0u64;
+
+    thread::scope(|scope| {
+        let append = thread::Builder::new().spawn_scoped(scope, || {
+            // the reader waits on this, so it must be set even if the
+            // appender panics
+            let _done = SetOnDrop(&done);
+            let mut buffer = vec![0u8; block_size];
+            let stopwatch = Instant::now();
+            for i in 0..count {
+                if interrupted() {
+                    break;
+                }
+
+                buffer.fill(fill(i));
+                with_retry(retries, || writer.write_all(&buffer)).unwrap();
+            }
+            writer.flush().unwrap();
+            stopwatch.elapsed()
+        });
+        // wasm32-wasi has no threads
+        let append = supported!(append);
+
+        let mut buffer = vec![0u8; block_size];
+        let stopwatch = Instant::now();
+
+        'blocks: for i in 0..count {
+            let mut filled = 0;
+            while filled < block_size {
+                // check for the writer finishing before reading, so a read
+                // hitting EOF after it finished really is the end
+                let finished = done.load(Ordering::Acquire);
+                let n = hint::black_box(
+                    with_retry(retries, || reader.read(hint::black_box(&mut buffer[filled..]))).unwrap()
+                );
+                if n == 0 {
+                    if finished {
+                        break 'blocks;
+                    }
+                    eof_waits += 1;
+                    thread::yield_now();
+                    continue;
+                }
+                if filled + n < block_size {
+                    partial_reads += 1;
+                }
+                filled += n;
+            }
+
+            if buffer.iter().any(|&x| x != fill(i)) {
+                torn += 1;
+            }
+            blocks += 1;
+        }
+
+        let duration = stopwatch.elapsed();
+        let append_duration = append.join().unwrap();
+
+        // Truncate the file! Otherwise Veracruz may try to copy it back over
+        // into the user's fs, which is a waste of (significant) time...
+        //
+        with_retry(retries, || File::create(&path)).unwrap().set_len(0).unwrap();
+
+        Report::new(duration)
+            .with_progress(config, blocks)
+            .with("append_runtime", append_duration.as_secs_f64())
+            .with("read_bytes_per_sec",
+                (blocks*u64::try_from(block_size).unwrap()) as f64
+                    / duration.as_secs_f64()
+            )
+            .with("partial_reads", partial_reads)
+            .with("torn_blocks", torn)
+            .with("eof_waits", eof_waits)
+    })
+}
+
+/// Append a large file's worth of blocks from one thread while another
+/// polls the file's length with metadata().len()
+///
+/// Programs watching a file grow do this. The writer publishes how many
+/// bytes it has written after each block, read just before each stat, so
+/// a length below that is stale, and the largest such lag is reported
+/// along with the cost of the stat calls, which are what is measured.
+pub fn length_poll(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/length_poll_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut writer = with_retry(retries, || File::create(&path)).unwrap();
+    let reader = with_retry(retries, || File::open(&path)).unwrap();
+    let mut prng = Prng::new(config.prng, seed);
+
+    let done = AtomicBool::new(false);
+    let written = AtomicU64::new(0);
+    let mut latencies = Vec::new();
+    let mut stale_polls = 0u64;
+    let mut max_lag = 0u64;
+    let mut last_len = 0u64;
+
+    thread::scope(|scope| {
+        let append = thread::Builder::new().spawn_scoped(scope, || {
+            // the poll loop runs until this is set, so it must be set even
+            // if the appender panics
+            let _done = SetOnDrop(&done);
+            let mut buffer = vec![0u8; block_size];
+            let mut bytes = 0u64;
+            let stopwatch = Instant::now();
+            for i in (0..size).step_by(block_size) {
+                if interrupted() {
+                    break;
+                }
+
+                let step_size = usize::try_from(
+                    min(i+u64::try_from(block_size).unwrap(), size) - i
+                ).unwrap();
+                for (j, x) in
+                    (&mut prng)
+                        .take(step_size)
+                        .enumerate()
+                {
+                    buffer[j] = x as u8;
+                }
+                mark(config, &mut buffer);
+
+                with_retry(retries, || writer.write_all(&buffer[..step_size])).unwrap();
+                bytes += u64::try_from(step_size).unwrap();
+                written.store(bytes, Ordering::Release);
+            }
+            writer.flush().unwrap();
+            stopwatch.elapsed()
+        });
+        // wasm32-wasi has no threads
+        let append = supported!(append);
+
+        loop {
+            // check for the writer finishing first, so the last poll sees
+            // everything it wrote
+            let finished = done.load(Ordering::Acquire);
+            let expected = written.load(Ordering::Acquire);
+
+            let stopwatch = Instant::now();
+            let len = hint::black_box(
+                with_retry(retries, || hint::black_box(&reader).metadata()).unwrap().len()
+            );
+            latencies.push(stopwatch.elapsed());
+
+            let lag = expected.saturating_sub(len);
+            if lag > 0 {
+                stale_polls += 1;
+                max_lag = max_lag.max(lag);
+            }
+            last_len = len;
+
+            if finished {
+                break;
+            }
+        }
+
+        let append_duration = append.join().unwrap();
+        let written = written.load(Ordering::Acquire);
+
+        // Truncate the file! Otherwise Veracruz may try to copy it back over
+        // into the user's fs, which is a waste of (significant) time...
+        //
+        with_retry(retries, || File::create(&path)).unwrap().set_len(0).unwrap();
+
+        let polls = u64::try_from(latencies.len()).unwrap();
+        let duration = latencies.iter().sum::<Duration>();
+        Report::new(duration)
+            .with("append_runtime", append_duration.as_secs_f64())
+            .with("polls", polls)
+            .with("polls_per_sec", polls as f64 / duration.as_secs_f64())
+            .with("stale_polls", stale_polls)
+            .with("max_lag_bytes", max_lag)
+            .with("final_length_visible", last_len == written)
+            .with_latencies("stat", &mut latencies)
+    })
+}
+
+/// Append size/block_size records of block_size bytes to one file from
+/// --threads threads, each with its own handle opened with append(true)
+///
+/// POSIX makes each O_APPEND write land whole at the end of the file, even
+/// with concurrent writers. Every record starts with its index and is
+/// filled with a byte derived from it, so afterwards the file is checked
+/// for records that were lost, repeated, or mixed with another record.
+pub fn concurrent_append(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, threads, .. } = *config;
+    let path = format!("{}/concurrent_append_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    with_retry(retries, || File::create(&path)).unwrap();
+
+    // non-zero, so unwritten holes don't pass as data
+    let fill = |i: u64| (i % 255) as u8 + 1;
+    let record = |buffer: &mut [u8], i: u64| {
+        buffer[..8].copy_from_slice(&i.to_le_bytes());
+        buffer[8..].fill(fill(i));
+    };
+
+    // each thread appends every threads'th record
+    let count = size/u64::try_from(block_size).unwrap();
+    let duration = timed_parallel(threads, |t| {
+        let mut file = with_retry(retries, || {
+            OpenOptions::new()
+                .append(true)
+                .open(&path)
+        }).unwrap();
+        let mut buffer = vec![0u8; block_size];
+
+        for i in (u64::from(t)..count).step_by(usize::try_from(threads).unwrap()) {
+            record(&mut buffer, i);
+
+            hint::black_box({
+                let input = hint::black_box(&buffer);
+                with_retry(retries, || file.write_all(input)).unwrap();
+            });
+        }
+
+        file.flush().unwrap();
+    });
+
+    // then check every record arrived exactly once and in one piece
+    let mut file = with_retry(retries, || File::open(&path)).unwrap();
+    let len = file.metadata().unwrap().len();
+    let mut seen = vec![false; usize::try_from(count).unwrap()];
+    let mut buffer = vec![0u8; block_size];
+    let mut expected = vec![0u8; block_size];
+    let mut corrupt = 0u64;
+    let mut repeated = 0u64;
+
+    for _ in 0..len/u64::try_from(block_size).unwrap() {
+        with_retry(retries, || file.read_exact(&mut buffer)).unwrap();
+        let i = u64::from_le_bytes(<[u8; 8]>::try_from(&buffer[..8]).unwrap());
+        if i >= count {
+            corrupt += 1;
+            continue;
+        }
+
+        record(&mut expected, i);
+        if buffer != expected {
+            corrupt += 1;
+        } else if mem::replace(&mut seen[usize::try_from(i).unwrap()], true) {
+            repeated += 1;
+        }
+    }
+
+    let lost = u64::try_from(seen.iter().filter(|&&seen| !seen).count()).unwrap();
+    let intact = len == count*u64::try_from(block_size).unwrap()
+        && lost == 0 && corrupt == 0 && repeated == 0;
+
+    mem::drop(file);
+    let file = with_retry(retries, || File::create(&path)).unwrap();
+
+    // Truncate the file! Otherwise Veracruz may try to copy it back over
+    // into the user's fs, which is a waste of (significant) time...
+    //
+    file.set_len(0).unwrap();
+
+    Report::new(duration)
+        .with("threads", threads)
+        .with("records", count)
+        .with("bytes_per_sec",
+            (count*u64::try_from(block_size).unwrap()) as f64 / duration.as_secs_f64()
+        )
+        .with("lost_records", lost)
+        .with("corrupt_records", corrupt)
+        .with("repeated_records", repeated)
+        .with("intact", intact)
+}
+
+/// Overwrite a large file in-order from one thread while another reads it
+/// in-order, both wrapping around at the end, for --duration seconds
+///
+/// This models a streaming producer and consumer sharing one inode. The
+/// reader can see any mix of old and new blocks, so the data isn't
+/// verified, only each side's throughput is reported.
+pub fn rw_interference(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/rw_interference_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
+    let mut prng = Prng::new(config.prng, seed);
+    let mut buffer = vec![0u8; block_size];
+    let limit = Duration::from_secs_f64(config.duration);
+
+    // first create/fill the file
+    for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
+        for (j, x) in
+            (&mut prng)
+                .take(step_size)
+                .enumerate()
+        {
+            buffer[j] = x as u8;
+        }
+        mark(config, &mut buffer);
+
+        with_retry(retries, || file.write_all(&buffer[..step_size])).unwrap();
+    }
+
+    mem::drop(file);
+    let mut writer = with_retry(retries, || {
+        OpenOptions::new()
+            .write(true)
+            .open(&path)
+    }).unwrap();
+    let mut reader = with_retry(retries, || File::open(&path)).unwrap();
+    let mut write_buffer = buffer.clone();
+
+    thread::scope(|scope| {
+        let overwrite = thread::Builder::new().spawn_scoped(scope, || {
+            let buffer = &mut write_buffer;
+            let mut written = 0u64;
+            let stopwatch = Instant::now();
+            'passes: while !interrupted() {
+                writer.seek(SeekFrom::Start(0)).unwrap();
+                for i in (0..size).step_by(block_size) {
+                    if stopwatch.elapsed() >= limit || interrupted() {
+                        break 'passes;
+                    }
+
+                    let step_size = usize::try_from(
+                        min(i+u64::try_from(block_size).unwrap(), size) - i
+                    ).unwrap();
+
+                    hint::black_box({
+                        let input = hint::black_box(&buffer[..step_size]);
+                        with_retry(retries, || writer.write_all(input)).unwrap();
+                    });
+                    written += u64::try_from(step_size).unwrap();
+                    // change the data each block, so it's really rewritten
+                    buffer[0] = buffer[0].wrapping_add(1);
+                }
+            }
+            writer.flush().unwrap();
+            (stopwatch.elapsed(), written)
+        });
+        // wasm32-wasi has no threads
+        let overwrite = supported!(overwrite);
+
+        let mut read = 0u64;
+        let stopwatch = Instant::now();
+        'passes: while !interrupted() {
+            reader.seek(SeekFrom::Start(0)).unwrap();
+            for i in (0..size).step_by(block_size) {
+                if stopwatch.elapsed() >= limit || interrupted() {
+                    break 'passes;
+                }
+
+                let step_size = usize::try_from(
+                    min(i+u64::try_from(block_size).unwrap(), size) - i
+                ).unwrap();
+
+                hint::black_box({
+                    with_retry(retries, || reader.read_exact(hint::black_box(&mut buffer[..step_size]))).unwrap();
+                    &buffer
+                });
+                read += u64::try_from(step_size).unwrap();
+            }
+        }
+
+        let duration = stopwatch.elapsed();
+        let (write_duration, written) = overwrite.join().unwrap();
+
+        // Truncate the file! Otherwise Veracruz may try to copy it back over
+        // into the user's fs, which is a waste of (significant) time...
+        //
+        with_retry(retries, || File::create(&path)).unwrap().set_len(0).unwrap();
+
+        Report::new(duration)
+            .with("duration_limit", config.duration)
+            .with("bytes_read", read)
+            .with("read_bytes_per_sec", read as f64 / duration.as_secs_f64())
+            .with("write_runtime", write_duration.as_secs_f64())
+            .with("bytes_written", written)
+            .with("write_bytes_per_sec", written as f64 / write_duration.as_secs_f64())
+    })
+}
+
+/// Copy a large file with std::io::copy, compared against copying it with
+/// a loop of block_size reads and writes
+///
+/// io::copy can use accelerated paths such as copy_file_range where the
+/// platform has them, so a large gap shows whether the VFS offers such a
+/// fast path. Both copies are checked against the source afterwards.
+pub fn io_copy(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/io_copy_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let loop_path = format!("{}/io_copy_{}_{}_{}_loop.txt", config.scratch_dir, size, block_size, run);
+    let copy_path = format!("{}/io_copy_{}_{}_{}_copy.txt", config.scratch_dir, size, block_size, run);
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
+    let mut prng = Prng::new(config.prng, seed);
+    let mut buffer = vec![0u8; block_size];
+
+    // first create/fill the file
+    for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
+        for (j, x) in
+            (&mut prng)
+                .take(step_size)
+                .enumerate()
+        {
+            buffer[j] = x as u8;
+        }
+        mark(config, &mut buffer);
+
+        with_retry(retries, || file.write_all(&buffer[..step_size])).unwrap();
+    }
+
+    mem::drop(file);
+
+    // then measure copying with a read/write loop
+    let mut src = with_retry(retries, || File::open(&path)).unwrap();
+    let mut dst = with_retry(retries, || File::create(&loop_path)).unwrap();
+    let stopwatch = Instant::now();
+
+    let mut loop_bytes = 0u64;
+    loop {
+        let n = with_retry(retries, || src.read(hint::black_box(&mut buffer))).unwrap();
+        if n == 0 {
+            break;
+        }
+
+        hint::black_box({
+            let input = hint::black_box(&buffer[..n]);
+            with_retry(retries, || dst.write_all(input)).unwrap();
+        });
+        loop_bytes += u64::try_from(n).unwrap();
+    }
+    dst.flush().unwrap();
+
+    let loop_duration = stopwatch.elapsed();
+
+    // and with io::copy
+    let mut src = with_retry(retries, || File::open(&path)).unwrap();
+    let mut dst = with_retry(retries, || File::create(&copy_path)).unwrap();
+    let stopwatch = Instant::now();
+
+    let copy_bytes = hint::black_box(
+        with_retry(retries, || io::copy(&mut src, &mut dst)).unwrap()
+    );
+    dst.flush().unwrap();
+
+    let duration = stopwatch.elapsed();
+
+    assert_eq!(loop_bytes, size);
+    assert_eq!(copy_bytes, size);
+
+    // then check, untimed, that both copies match the source
+    let mut src = with_retry(retries, || File::open(&path)).unwrap();
+    let mut copies = [&loop_path, &copy_path]
+        .map(|path| with_retry(retries, || File::open(path)).unwrap());
+    let mut expected = vec![0u8; block_size];
+    let mut mismatched_blocks = 0u64;
+    for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
+        with_retry(retries, || src.read_exact(&mut expected[..step_size])).unwrap();
+        for copy in &mut copies {
+            with_retry(retries, || copy.read_exact(&mut buffer[..step_size])).unwrap();
+            if buffer[..step_size] != expected[..step_size] {
+                mismatched_blocks += 1;
+            }
+        }
+    }
+
+    mem::drop(src);
+    mem::drop(copies);
+
+    // Truncate the files! Otherwise Veracruz may try to copy them back over
+    // into the user's fs, which is a waste of (significant) time...
+    //
+    for path in [&path, &loop_path, &copy_path] {
+        with_retry(retries, || File::create(path)).unwrap().set_len(0).unwrap();
+    }
+
+    Report::new(duration)
+        .with("bytes_per_sec", size as f64 / duration.as_secs_f64())
+        .with("loop_runtime", loop_duration.as_secs_f64())
+        .with("loop_bytes_per_sec", size as f64 / loop_duration.as_secs_f64())
+        .with("copy_speedup",
+            loop_duration.as_secs_f64() / duration.as_secs_f64()
+        )
+        .with("mismatched_blocks", mismatched_blocks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::assert_partial_block;
+
+    #[test]
+    fn write_inorder_partial_block() {
+        assert_partial_block("write_inorder", write_inorder);
+    }
+
+    #[test]
+    fn write_reversed_partial_block() {
+        assert_partial_block("write_reversed", write_reversed);
+    }
 }
@@ -0,0 +1,316 @@
+//! Benchmark of operations on file handles themselves
+//!
+//! ## Authors
+//!
+//! The Veracruz Development Team.
+//!
+//! ## Copyright
+//!
+//! See the file `LICENSING.markdown` in the Veracruz root directory for licensing
+//! and copyright information.
+
+use crate::{
+    Config,
+    hint,
+    marker::mark,
+    report::Report,
+    report::supported,
+    retry::with_retry,
+    scratch,
+    rng::Prng,
+};
+use std::{
+    cmp::min,
+    convert::TryFrom,
+    fs,
+    fs::File,
+    fs::OpenOptions,
+    io::Write,
+    io::Read,
+    io::Seek,
+    io::SeekFrom,
+    io::BufWriter,
+    mem,
+    time::Duration,
+    time::Instant,
+};
+
+
+/// Clone a file handle and read through both handles, checking whether the
+/// clones share a file offset (as dup'd descriptors do in POSIX)
+pub fn clone_offsets(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/clone_offsets_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
+    let mut prng = Prng::new(config.prng, seed);
+    let mut buffer = vec![0u8; block_size];
+
+    // first create/fill the file
+    for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
+        for (j, x) in
+            (&mut prng)
+                .take(step_size)
+                .enumerate()
+        {
+            buffer[j] = x as u8;
+        }
+        mark(config, &mut buffer);
+
+        with_retry(retries, || file.write_all(&buffer[..step_size])).unwrap();
+    }
+
+    mem::drop(file);
+
+    // read back the first two blocks for reference
+    let mut file = with_retry(retries, || File::open(&path)).unwrap();
+    let mut expected = vec![0u8; 2*block_size];
+    with_retry(retries, || file.read_exact(&mut expected)).unwrap();
+    mem::drop(file);
+
+    let mut file = with_retry(retries, || File::open(&path)).unwrap();
+    let mut clone_buffer = vec![0u8; block_size];
+
+    // now measure cloning, and a read through each handle
+    let stopwatch = Instant::now();
+
+    let mut clone = hint::black_box(supported!(
+        file.try_clone(),
+        with_retry(retries, || File::create(&path)).unwrap()
+    ));
+    let clone_duration = stopwatch.elapsed();
+
+    hint::black_box({
+        with_retry(retries, || file.read_exact(hint::black_box(&mut buffer))).unwrap();
+        with_retry(retries, || clone.read_exact(hint::black_box(&mut clone_buffer))).unwrap();
+        (&buffer, &clone_buffer)
+    });
+
+    let duration = stopwatch.elapsed();
+
+    // with a shared offset the clone continues where the original stopped
+    assert_eq!(buffer, expected[..block_size]);
+    let shared_offset = clone_buffer == expected[block_size..];
+    let independent_offset = clone_buffer == expected[..block_size];
+
+    mem::drop(clone);
+    mem::drop(file);
+    let file = with_retry(retries, || File::create(&path)).unwrap();
+
+    // Truncate the file! Otherwise Veracruz may try to copy it back over
+    // into the user's fs, which is a waste of (significant) time...
+    //
+    file.set_len(0).unwrap();
+
+    Report::new(duration)
+        .with("clone_runtime", clone_duration.as_secs_f64())
+        .with("shared_offset", shared_offset)
+        .with("independent_offset", independent_offset)
+}
+
+/// Open one file twice at once, write-only and read-only, then write each
+/// block through the first handle and read it back through the second
+///
+/// Open flags decide the capabilities a handle carries, so this checks the
+/// two handles stay coherent, counting blocks whose write the read didn't
+/// see. Writes and reads are timed separately.
+pub fn mixed_flags(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/mixed_flags_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut prng = Prng::new(config.prng, seed);
+    let mut buffer = vec![0u8; block_size];
+    let mut readback = vec![0u8; block_size];
+
+    // first create the file, zeroed, so every block's write is a change;
+    // it's opened for reading too, since the flags here set the persistent
+    // capabilities and the read-only handle needs them
+    let file = with_retry(retries, || {
+        OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)
+    }).unwrap();
+    file.set_len(size).unwrap();
+    mem::drop(file);
+
+    let stopwatch = Instant::now();
+    let (mut writer, mut reader) = hint::black_box((
+        supported!(
+            with_retry(retries, || OpenOptions::new().write(true).open(&path)),
+            with_retry(retries, || File::create(&path)).unwrap()
+        ),
+        supported!(
+            with_retry(retries, || OpenOptions::new().read(true).open(&path)),
+            with_retry(retries, || File::create(&path)).unwrap()
+        ),
+    ));
+    let open_duration = stopwatch.elapsed();
+
+    let mut write_duration = Duration::ZERO;
+    let mut read_duration = Duration::ZERO;
+    let mut stale = 0u64;
+    let mut blocks = 0u64;
+
+    for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
+        for (j, x) in
+            (&mut prng)
+                .take(step_size)
+                .enumerate()
+        {
+            buffer[j] = x as u8;
+        }
+        mark(config, &mut buffer);
+
+        let stopwatch = Instant::now();
+        hint::black_box({
+            writer.seek(SeekFrom::Start(i)).unwrap();
+
+            let input = hint::black_box(&buffer[..step_size]);
+            with_retry(retries, || writer.write_all(input)).unwrap();
+            writer.flush().unwrap();
+        });
+        write_duration += stopwatch.elapsed();
+
+        let stopwatch = Instant::now();
+        hint::black_box({
+            reader.seek(SeekFrom::Start(i)).unwrap();
+
+            with_retry(retries, || reader.read_exact(hint::black_box(&mut readback[..step_size]))).unwrap();
+            &readback
+        });
+        read_duration += stopwatch.elapsed();
+
+        if readback[..step_size] != buffer[..step_size] {
+            stale += 1;
+        }
+        blocks += 1;
+    }
+
+    mem::drop(reader);
+    mem::drop(writer);
+    let file = with_retry(retries, || File::create(&path)).unwrap();
+
+    // Truncate the file! Otherwise Veracruz may try to copy it back over
+    // into the user's fs, which is a waste of (significant) time...
+    //
+    file.set_len(0).unwrap();
+
+    Report::new(write_duration + read_duration)
+        .with("blocks", blocks)
+        .with("open_runtime", open_duration.as_secs_f64())
+        .with("write_runtime", write_duration.as_secs_f64())
+        .with("read_runtime", read_duration.as_secs_f64())
+        .with("stale_reads", stale)
+        .with("writes_visible", stale == 0)
+}
+
+/// Repeatedly duplicate a file handle with File::try_clone
+///
+/// This isolates the cost of replicating handle state in the capability
+/// layer. Failed clones are counted rather than aborting the run.
+pub fn clone_handle(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, .. } = *config;
+    let path = format!("{}/clone_handle_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let file = with_retry(retries, || File::create(&path)).unwrap();
+
+    let mut failures = 0u64;
+    let stopwatch = Instant::now();
+
+    let count = size/u64::try_from(block_size).unwrap();
+    for _ in 0..count {
+        let clone = hint::black_box(file.try_clone());
+        if clone.is_err() {
+            failures += 1;
+        }
+        mem::drop(clone);
+    }
+
+    let duration = stopwatch.elapsed();
+
+    // Truncate the file! Otherwise Veracruz may try to copy it back over
+    // into the user's fs, which is a waste of (significant) time...
+    //
+    file.set_len(0).unwrap();
+
+    Report::new(duration)
+        .with("clones", count)
+        .with("clone_failures", failures)
+        .with("clones_per_sec", count as f64 / duration.as_secs_f64())
+}
+
+/// Open size/block_size files and keep them all open, timing each open as
+/// the number of open handles grows
+///
+/// This shows whether open cost degrades with the number of concurrently
+/// open handles, and where the VFS runs out of handles. An open that fails
+/// stops the run rather than aborting it, and the number of handles we
+/// managed to hold is reported.
+pub fn fd_pressure(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, .. } = *config;
+    let path = format!("{}/fd_pressure_{}_{}_{}", config.scratch_dir, size, block_size, run);
+    scratch::create_dir(config, &path);
+
+    // first create the files, empty since only opening is measured
+    let count = size/u64::try_from(block_size).unwrap();
+    for i in 0..count {
+        let path = format!("{}/{:09x}.txt", path, i);
+        with_retry(retries, || File::create(&path)).unwrap();
+    }
+
+    // then open them all, holding on to each handle
+    let mut handles = Vec::new();
+    let mut latencies = Vec::new();
+    let mut open_error = None;
+    let stopwatch = Instant::now();
+
+    for i in 0..count {
+        let path = format!("{}/{:09x}.txt", path, i);
+
+        let open_stopwatch = Instant::now();
+        let file = hint::black_box(
+            with_retry(retries, || File::open(hint::black_box(&path)))
+        );
+        let latency = open_stopwatch.elapsed();
+
+        match file {
+            Ok(file) => {
+                handles.push(file);
+                latencies.push(latency);
+            }
+            Err(err) => {
+                open_error = Some(err.to_string());
+                break;
+            }
+        }
+    }
+
+    let duration = stopwatch.elapsed();
+    let max_open = handles.len();
+
+    // closing is timed separately
+    let stopwatch = Instant::now();
+    mem::drop(hint::black_box(handles));
+    let close_duration = stopwatch.elapsed();
+
+    // Clean up! Otherwise Veracruz may try to copy it back over
+    // into the user's fs, which is a waste of (significant) time...
+    //
+    fs::remove_dir_all(&path).unwrap();
+
+    let report = Report::new(duration)
+        .with("max_open", max_open)
+        .with("close_runtime", close_duration.as_secs_f64())
+        .with_latencies("open", &mut latencies);
+
+    match open_error {
+        Some(err) => report.with("open_error", err),
+        None => report,
+    }
+}
@@ -0,0 +1,26 @@
+//! Optimization barrier used by all benchmarks
+//!
+//! ## Authors
+//!
+//! The Veracruz Development Team.
+//!
+//! ## Copyright
+//!
+//! See the file `LICENSING.markdown` in the Veracruz root directory for licensing
+//! and copyright information.
+
+/// Prevents the optimizer from eliding the I/O we are trying to measure
+#[cfg(not(feature = "no_black_box"))]
+pub use std::hint::black_box;
+
+/// Identity in place of std::hint::black_box, only useful to measure the
+/// overhead of black_box itself, since without it the optimizer is free to
+/// elide the I/O we are trying to measure
+///
+/// This is only a build-time switch, a runtime one would add a branch to
+/// every timed operation.
+#[cfg(feature = "no_black_box")]
+#[inline(always)]
+pub fn black_box<T>(x: T) -> T {
+    x
+}
@@ -10,127 +10,142 @@
 //! See the file `LICENSING.markdown` in the Veracruz root directory for licensing
 //! and copyright information.
 
+use crate::{
+    Config,
+    coverage::Coverage,
+    hint,
+    marker::mark,
+    phases::{Phase, Phases},
+    file::warm_up,
+    report::Report,
+    retry::with_retry,
+    rng::Prng,
+};
 use std::{
     cell::RefCell,
     cmp::min,
     convert::TryFrom,
+    fs,
     fs::File,
     fs::OpenOptions,
-    hint,
     io::Write,
     io::Read,
     io::Seek,
     io::SeekFrom,
     io::BufWriter,
-    iter,
     mem,
     ops::DerefMut,
-    time::Duration,
     time::Instant,
 };
 
-/// xorshift64 for providing deterministic pseudo-random numbers
-fn xorshift64(seed: u64) -> impl Iterator<Item=u64> {
-    let mut x = seed;
-    iter::repeat_with(move || {
-        x ^= x << 13;
-        x ^= x >> 7;
-        x ^= x << 17;
-        x
-    })
-}
-
 
 /// Write a large file in-order
-pub fn write_inorder(size: u64, block_size: usize, run: u32) -> Duration {
-    let path = format!("/scratch/incremental_write_inorder_{}_{}_{}.txt", size, block_size, run);
-    let mut prng = xorshift64(42);
+pub fn write_inorder(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/incremental_write_inorder_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut prng = Prng::new(config.prng, seed);
     let mut buffer = vec![0u8; block_size];
 
+    let mut phases = Phases::new(config);
     let stopwatch = Instant::now();
 
     for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
         for (j, x) in
             (&mut prng)
-                .take(usize::try_from(
-                    min(i+u64::try_from(block_size).unwrap(), size) - i
-                ).unwrap())
+                .take(step_size)
                 .enumerate()
         {
             buffer[j] = x as u8;
         }
+        mark(config, &mut buffer);
 
         
         hint::black_box({
-            let mut file = OpenOptions::new()
-                .write(true)
-                .create(true)
-                .append(true)
-                .open(&path).unwrap();
-            let input = hint::black_box(&buffer);
-            file.write_all(input).unwrap();
-            file.flush().unwrap();
+            let mut file = phases.time(Phase::Open, || with_retry(retries, || {
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&path)
+            })).unwrap();
+            let input = hint::black_box(&buffer[..step_size]);
+            phases.time(Phase::Io, || with_retry(retries, || file.write_all(input))).unwrap();
+            phases.time(Phase::Flush, || file.flush()).unwrap();
         });
     }
 
     let duration = stopwatch.elapsed();
 
+    let metadata = fs::metadata(&path).unwrap();
+
     // Truncate the file! Otherwise Veracruz may try to copy it back over
     // into the user's fs, which is a waste of (significant) time...
     //
-    let file = File::create(&path).unwrap();
+    let file = with_retry(retries, || File::create(&path)).unwrap();
     file.set_len(0).unwrap();
 
-    duration
+    Report::new(duration)
+        .with_phases(&phases)
+        .with_footprint(&metadata)
 }
 
 /// Update a large file in-order
-pub fn update_inorder(size: u64, block_size: usize, run: u32) -> Duration {
-    let path = format!("/scratch/incremental_update_inorder_{}_{}_{}.txt", size, block_size, run);
-    let mut file = BufWriter::new(File::create(&path).unwrap());
-    let mut prng = xorshift64(42);
+pub fn update_inorder(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/incremental_update_inorder_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
+    let mut prng = Prng::new(config.prng, seed);
     let mut buffer = vec![0u8; block_size];
 
     // first create/fill the file
     for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
         for (j, x) in
             (&mut prng)
-                .take(usize::try_from(
-                    min(i+u64::try_from(block_size).unwrap(), size) - i
-                ).unwrap())
+                .take(step_size)
                 .enumerate()
         {
             buffer[j] = x as u8;
         }
+        mark(config, &mut buffer);
 
-        file.write_all(&buffer).unwrap();
+        with_retry(retries, || file.write_all(&buffer[..step_size])).unwrap();
     }
 
     mem::drop(file);
 
     // now measure updates
+    let mut phases = Phases::new(config);
     let stopwatch = Instant::now();
 
     for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
         for (j, x) in
             (&mut prng)
-                .take(usize::try_from(
-                    min(i+u64::try_from(block_size).unwrap(), size) - i
-                ).unwrap())
+                .take(step_size)
                 .enumerate()
         {
             buffer[j] = x as u8;
         }
+        mark(config, &mut buffer);
 
         
         hint::black_box({
-            let mut file = OpenOptions::new()
-                .write(true)
-                .open(&path).unwrap();
-            file.seek(SeekFrom::Start(i)).unwrap();
-            let input = hint::black_box(&buffer);
-            file.write_all(input).unwrap();
-            file.flush().unwrap();
+            let mut file = phases.time(Phase::Open, || with_retry(retries, || {
+                OpenOptions::new()
+                    .write(true)
+                    .open(&path)
+            })).unwrap();
+            phases.time(Phase::Seek, || file.seek(SeekFrom::Start(i))).unwrap();
+            let input = hint::black_box(&buffer[..step_size]);
+            phases.time(Phase::Io, || with_retry(retries, || file.write_all(input))).unwrap();
+            phases.time(Phase::Flush, || file.flush()).unwrap();
         });
     }
 
@@ -139,37 +154,42 @@ pub fn update_inorder(size: u64, block_size: usize, run: u32) -> Duration {
     // Truncate the file! Otherwise Veracruz may try to copy it back over
     // into the user's fs, which is a waste of (significant) time...
     //
-    let file = File::create(&path).unwrap();
+    let file = with_retry(retries, || File::create(&path)).unwrap();
     file.set_len(0).unwrap();
 
-    duration
+    Report::new(duration)
+        .with_phases(&phases)
 }
 
 /// Read a large file in-order
-pub fn read_inorder(size: u64, block_size: usize, run: u32) -> Duration {
-    let path = format!("/scratch/incremental_read_inorder_{}_{}_{}.txt", size, block_size, run);
-    let mut file = BufWriter::new(File::create(&path).unwrap());
-    let mut prng = xorshift64(42);
+pub fn read_inorder(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/incremental_read_inorder_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
+    let mut prng = Prng::new(config.prng, seed);
     let mut buffer = vec![0u8; block_size];
 
     // first create/fill the file
     for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
         for (j, x) in
             (&mut prng)
-                .take(usize::try_from(
-                    min(i+u64::try_from(block_size).unwrap(), size) - i
-                ).unwrap())
+                .take(step_size)
                 .enumerate()
         {
             buffer[j] = x as u8;
         }
+        mark(config, &mut buffer);
 
-        file.write_all(&buffer).unwrap();
+        with_retry(retries, || file.write_all(&buffer[..step_size])).unwrap();
     }
 
     mem::drop(file);
 
     // Now measure reads
+    let mut phases = Phases::new(config);
     let stopwatch = Instant::now();
 
     for i in (0..size).step_by(block_size) {
@@ -178,11 +198,13 @@ pub fn read_inorder(size: u64, block_size: usize, run: u32) -> Duration {
         ).unwrap();
         
         hint::black_box({
-            let mut file = OpenOptions::new()
-                .read(true)
-                .open(&path).unwrap();
-            file.seek(SeekFrom::Start(i)).unwrap();
-            file.read_exact(hint::black_box(&mut buffer[..step_size])).unwrap();
+            let mut file = phases.time(Phase::Open, || with_retry(retries, || {
+                OpenOptions::new()
+                    .read(true)
+                    .open(&path)
+            })).unwrap();
+            phases.time(Phase::Seek, || file.seek(SeekFrom::Start(i))).unwrap();
+            phases.time(Phase::Io, || with_retry(retries, || file.read_exact(hint::black_box(&mut buffer[..step_size])))).unwrap();
             &buffer
         });
     }
@@ -192,112 +214,132 @@ pub fn read_inorder(size: u64, block_size: usize, run: u32) -> Duration {
     // Truncate the file! Otherwise Veracruz may try to copy it back over
     // into the user's fs, which is a waste of (significant) time...
     //
-    let file = File::create(&path).unwrap();
+    let file = with_retry(retries, || File::create(&path)).unwrap();
     file.set_len(0).unwrap();
 
-    duration
+    Report::new(duration)
+        .with_phases(&phases)
 }
 
 /// Write a large file in reverse-order
-pub fn write_reversed(size: u64, block_size: usize, run: u32) -> Duration {
-    let path = format!("/scratch/incremental_write_reversed_{}_{}_{}.txt", size, block_size, run);
-    let mut prng = xorshift64(42);
+pub fn write_reversed(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/incremental_write_reversed_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut prng = Prng::new(config.prng, seed);
     let mut buffer = vec![0u8; block_size];
 
+    let mut phases = Phases::new(config);
     let stopwatch = Instant::now();
 
-    // this division is a workaround for Range<u64> limitations
+    // this division is a workaround for Range<u64> limitations, rounded
+    // up so a trailing partial block is included
     for i in
-        (0..size/u64::try_from(block_size).unwrap())
+        (0..size.div_ceil(u64::try_from(block_size).unwrap()))
             .rev()
             .map(|i| i*u64::try_from(block_size).unwrap())
     {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
         for (j, x) in
             (&mut prng)
-                .take(usize::try_from(
-                    min(i+u64::try_from(block_size).unwrap(), size) - i
-                ).unwrap())
+                .take(step_size)
                 .enumerate()
         {
             buffer[j] = x as u8;
         }
+        mark(config, &mut buffer);
 
         
         hint::black_box({
-            let mut file = OpenOptions::new()
+            let mut file = phases.time(Phase::Open, || OpenOptions::new()
                 .write(true)
                 .create(true)
-                .open(&path).unwrap();
-            file.seek(SeekFrom::Start(i)).unwrap();
-            let input = hint::black_box(&buffer);
-            file.write_all(input).unwrap();
-            file.flush().unwrap();
+                .truncate(false)
+                .open(&path)).unwrap();
+            phases.time(Phase::Seek, || file.seek(SeekFrom::Start(i))).unwrap();
+            let input = hint::black_box(&buffer[..step_size]);
+            phases.time(Phase::Io, || with_retry(retries, || file.write_all(input))).unwrap();
+            phases.time(Phase::Flush, || file.flush()).unwrap();
         });
     }
 
     let duration = stopwatch.elapsed();
 
+    let metadata = fs::metadata(&path).unwrap();
+
     // Truncate the file! Otherwise Veracruz may try to copy it back over
     // into the user's fs, which is a waste of (significant) time...
     //
-    let file = File::create(&path).unwrap();
+    let file = with_retry(retries, || File::create(&path)).unwrap();
     file.set_len(0).unwrap();
 
-    duration
+    Report::new(duration)
+        .with_phases(&phases)
+        .with_footprint(&metadata)
 }
 
 /// Update a large file in reverse-order
-pub fn update_reversed(size: u64, block_size: usize, run: u32) -> Duration {
-    let path = format!("/scratch/incremental_update_reversed_{}_{}_{}.txt", size, block_size, run);
-    let mut file = BufWriter::new(File::create(&path).unwrap());
-    let mut prng = xorshift64(42);
+pub fn update_reversed(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/incremental_update_reversed_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
+    let mut prng = Prng::new(config.prng, seed);
     let mut buffer = vec![0u8; block_size];
 
     // first create/fill the file
     for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
         for (j, x) in
             (&mut prng)
-                .take(usize::try_from(
-                    min(i+u64::try_from(block_size).unwrap(), size) - i
-                ).unwrap())
+                .take(step_size)
                 .enumerate()
         {
             buffer[j] = x as u8;
         }
+        mark(config, &mut buffer);
 
-        file.write_all(&buffer).unwrap();
+        with_retry(retries, || file.write_all(&buffer[..step_size])).unwrap();
     }
 
     mem::drop(file);
 
     // now measure updates
+    let mut phases = Phases::new(config);
     let stopwatch = Instant::now();
 
-    // this division is a workaround for Range<u64> limitations
+    // this division is a workaround for Range<u64> limitations, rounded
+    // up so a trailing partial block is included
     for i in
-        (0..size/u64::try_from(block_size).unwrap())
+        (0..size.div_ceil(u64::try_from(block_size).unwrap()))
             .rev()
             .map(|i| i*u64::try_from(block_size).unwrap())
     {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
         for (j, x) in
             (&mut prng)
-                .take(usize::try_from(
-                    min(i+u64::try_from(block_size).unwrap(), size) - i
-                ).unwrap())
+                .take(step_size)
                 .enumerate()
         {
             buffer[j] = x as u8;
         }
+        mark(config, &mut buffer);
 
         
         hint::black_box({
-            let mut file = OpenOptions::new()
-                .write(true)
-                .open(&path).unwrap();
-            file.seek(SeekFrom::Start(i)).unwrap();
-            let input = hint::black_box(&buffer);
-            file.write_all(input).unwrap();
-            file.flush().unwrap();
+            let mut file = phases.time(Phase::Open, || with_retry(retries, || {
+                OpenOptions::new()
+                    .write(true)
+                    .open(&path)
+            })).unwrap();
+            phases.time(Phase::Seek, || file.seek(SeekFrom::Start(i))).unwrap();
+            let input = hint::black_box(&buffer[..step_size]);
+            phases.time(Phase::Io, || with_retry(retries, || file.write_all(input))).unwrap();
+            phases.time(Phase::Flush, || file.flush()).unwrap();
         });
     }
 
@@ -306,42 +348,48 @@ pub fn update_reversed(size: u64, block_size: usize, run: u32) -> Duration {
     // Truncate the file! Otherwise Veracruz may try to copy it back over
     // into the user's fs, which is a waste of (significant) time...
     //
-    let file = File::create(&path).unwrap();
+    let file = with_retry(retries, || File::create(&path)).unwrap();
     file.set_len(0).unwrap();
 
-    duration
+    Report::new(duration)
+        .with_phases(&phases)
 }
 
 /// Read a large file in reverse-order
-pub fn read_reversed(size: u64, block_size: usize, run: u32) -> Duration {
-    let path = format!("/scratch/incremental_read_reversed_{}_{}_{}.txt", size, block_size, run);
-    let mut file = BufWriter::new(File::create(&path).unwrap());
-    let mut prng = xorshift64(42);
+pub fn read_reversed(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/incremental_read_reversed_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
+    let mut prng = Prng::new(config.prng, seed);
     let mut buffer = vec![0u8; block_size];
 
     // first create/fill the file
     for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
         for (j, x) in
             (&mut prng)
-                .take(usize::try_from(
-                    min(i+u64::try_from(block_size).unwrap(), size) - i
-                ).unwrap())
+                .take(step_size)
                 .enumerate()
         {
             buffer[j] = x as u8;
         }
+        mark(config, &mut buffer);
 
-        file.write_all(&buffer).unwrap();
+        with_retry(retries, || file.write_all(&buffer[..step_size])).unwrap();
     }
 
     mem::drop(file);
 
     // Now measure reads
+    let mut phases = Phases::new(config);
     let stopwatch = Instant::now();
 
-    // this division is a workaround for Range<u64> limitations
+    // this division is a workaround for Range<u64> limitations, rounded
+    // up so a trailing partial block is included
     for i in
-        (0..size/u64::try_from(block_size).unwrap())
+        (0..size.div_ceil(u64::try_from(block_size).unwrap()))
             .rev()
             .map(|i| i*u64::try_from(block_size).unwrap())
     {
@@ -350,11 +398,13 @@ pub fn read_reversed(size: u64, block_size: usize, run: u32) -> Duration {
         ).unwrap();
         
         hint::black_box({
-            let mut file = OpenOptions::new()
-                .read(true)
-                .open(&path).unwrap();
-            file.seek(SeekFrom::Start(i)).unwrap();
-            file.read_exact(hint::black_box(&mut buffer[..step_size])).unwrap();
+            let mut file = phases.time(Phase::Open, || with_retry(retries, || {
+                OpenOptions::new()
+                    .read(true)
+                    .open(&path)
+            })).unwrap();
+            phases.time(Phase::Seek, || file.seek(SeekFrom::Start(i))).unwrap();
+            phases.time(Phase::Io, || with_retry(retries, || file.read_exact(hint::black_box(&mut buffer[..step_size])))).unwrap();
             &buffer
         });
     }
@@ -364,120 +414,143 @@ pub fn read_reversed(size: u64, block_size: usize, run: u32) -> Duration {
     // Truncate the file! Otherwise Veracruz may try to copy it back over
     // into the user's fs, which is a waste of (significant) time...
     //
-    let file = File::create(&path).unwrap();
+    let file = with_retry(retries, || File::create(&path)).unwrap();
     file.set_len(0).unwrap();
 
-    duration
+    Report::new(duration)
+        .with_phases(&phases)
 }
 
 /// Write a large file in reverse-order
-pub fn write_random(size: u64, block_size: usize, run: u32) -> Duration {
-    let path = format!("/scratch/incremental_write_random_{}_{}_{}.txt", size, block_size, run);
-    let prng = RefCell::new(xorshift64(42));
+pub fn write_random(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/incremental_write_random_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let prng = RefCell::new(Prng::new(config.prng, seed));
     let mut buffer = vec![0u8; block_size];
 
+    let mut phases = Phases::new(config);
     let stopwatch = Instant::now();
 
     // this may not touch every block, but that's ok
     let count = size/u64::try_from(block_size).unwrap();
+    let mut coverage = Coverage::new(count);
     for i in 
         (0..count)
             .map(|_| prng.borrow_mut().next().unwrap() % count)
             .map(|i| i*u64::try_from(block_size).unwrap())
     {
+        coverage.touch(i/u64::try_from(block_size).unwrap());
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
         for (j, x) in
             prng
                 .borrow_mut()
                 .deref_mut()
-                .take(usize::try_from(
-                    min(i+u64::try_from(block_size).unwrap(), size) - i
-                ).unwrap())
+                .take(step_size)
                 .enumerate()
         {
             buffer[j] = x as u8;
         }
+        mark(config, &mut buffer);
 
         
         hint::black_box({
-            let mut file = OpenOptions::new()
+            let mut file = phases.time(Phase::Open, || OpenOptions::new()
                 .write(true)
                 .create(true)
-                .open(&path).unwrap();
-            file.seek(SeekFrom::Start(i)).unwrap();
-            let input = hint::black_box(&buffer);
-            file.write_all(input).unwrap();
-            file.flush().unwrap();
+                .truncate(false)
+                .open(&path)).unwrap();
+            phases.time(Phase::Seek, || file.seek(SeekFrom::Start(i))).unwrap();
+            let input = hint::black_box(&buffer[..step_size]);
+            phases.time(Phase::Io, || with_retry(retries, || file.write_all(input))).unwrap();
+            phases.time(Phase::Flush, || file.flush()).unwrap();
         });
     }
 
     let duration = stopwatch.elapsed();
 
+    let metadata = fs::metadata(&path).unwrap();
+
     // Truncate the file! Otherwise Veracruz may try to copy it back over
     // into the user's fs, which is a waste of (significant) time...
     //
-    let file = File::create(&path).unwrap();
+    let file = with_retry(retries, || File::create(&path)).unwrap();
     file.set_len(0).unwrap();
 
-    duration
+    Report::new(duration)
+        .with_phases(&phases)
+        .with_coverage(&coverage)
+        .with_footprint(&metadata)
 }
 
 /// Update a large file in reverse-order
-pub fn update_random(size: u64, block_size: usize, run: u32) -> Duration {
-    let path = format!("/scratch/incremental_update_random_{}_{}_{}.txt", size, block_size, run);
-    let mut file = BufWriter::new(File::create(&path).unwrap());
-    let prng = RefCell::new(xorshift64(42));
+pub fn update_random(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/incremental_update_random_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
+    let prng = RefCell::new(Prng::new(config.prng, seed));
     let mut buffer = vec![0u8; block_size];
 
     // first create/fill the file
     for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
         for (j, x) in
             prng
                 .borrow_mut()
                 .deref_mut()
-                .take(usize::try_from(
-                    min(i+u64::try_from(block_size).unwrap(), size) - i
-                ).unwrap())
+                .take(step_size)
                 .enumerate()
         {
             buffer[j] = x as u8;
         }
+        mark(config, &mut buffer);
 
-        file.write_all(&buffer).unwrap();
+        with_retry(retries, || file.write_all(&buffer[..step_size])).unwrap();
     }
 
     mem::drop(file);
 
     // now measure updates
+    let mut phases = Phases::new(config);
     let stopwatch = Instant::now();
 
     // this may not touch every block, but that's ok
     let count = size/u64::try_from(block_size).unwrap();
+    let mut coverage = Coverage::new(count);
     for i in 
         (0..count)
             .map(|_| prng.borrow_mut().next().unwrap() % count)
             .map(|i| i*u64::try_from(block_size).unwrap())
     {
+        coverage.touch(i/u64::try_from(block_size).unwrap());
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
         for (j, x) in
             prng
                 .borrow_mut()
                 .deref_mut()
-                .take(usize::try_from(
-                    min(i+u64::try_from(block_size).unwrap(), size) - i
-                ).unwrap())
+                .take(step_size)
                 .enumerate()
         {
             buffer[j] = x as u8;
         }
+        mark(config, &mut buffer);
 
         
         hint::black_box({
-            let mut file = OpenOptions::new()
-                .write(true)
-                .open(&path).unwrap();
-            file.seek(SeekFrom::Start(i)).unwrap();
-            let input = hint::black_box(&buffer);
-            file.write_all(input).unwrap();
-            file.flush().unwrap();
+            let mut file = phases.time(Phase::Open, || with_retry(retries, || {
+                OpenOptions::new()
+                    .write(true)
+                    .open(&path)
+            })).unwrap();
+            phases.time(Phase::Seek, || file.seek(SeekFrom::Start(i))).unwrap();
+            let input = hint::black_box(&buffer[..step_size]);
+            phases.time(Phase::Io, || with_retry(retries, || file.write_all(input))).unwrap();
+            phases.time(Phase::Flush, || file.flush()).unwrap();
         });
     }
 
@@ -486,56 +559,72 @@ pub fn update_random(size: u64, block_size: usize, run: u32) -> Duration {
     // Truncate the file! Otherwise Veracruz may try to copy it back over
     // into the user's fs, which is a waste of (significant) time...
     //
-    let file = File::create(&path).unwrap();
+    let file = with_retry(retries, || File::create(&path)).unwrap();
     file.set_len(0).unwrap();
 
-    duration
+    Report::new(duration)
+        .with_phases(&phases)
+        .with_coverage(&coverage)
 }
 
 /// Read a large file in reverse-order
-pub fn read_random(size: u64, block_size: usize, run: u32) -> Duration {
-    let path = format!("/scratch/incremental_read_random_{}_{}_{}.txt", size, block_size, run);
-    let mut file = BufWriter::new(File::create(&path).unwrap());
-    let mut prng = xorshift64(42);
+pub fn read_random(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, warmup, .. } = *config;
+    let path = format!("{}/incremental_read_random_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut file = BufWriter::new(with_retry(retries, || File::create(&path)).unwrap());
+    let mut prng = Prng::new(config.prng, seed);
     let mut buffer = vec![0u8; block_size];
 
     // first create/fill the file
     for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
         for (j, x) in
             (&mut prng)
-                .take(usize::try_from(
-                    min(i+u64::try_from(block_size).unwrap(), size) - i
-                ).unwrap())
+                .take(step_size)
                 .enumerate()
         {
             buffer[j] = x as u8;
         }
+        mark(config, &mut buffer);
 
-        file.write_all(&buffer).unwrap();
+        with_retry(retries, || file.write_all(&buffer[..step_size])).unwrap();
     }
 
     mem::drop(file);
 
+    // optionally read everything once first, so we measure warm reads
+    if warmup {
+        let mut file = with_retry(retries, || File::open(&path)).unwrap();
+        warm_up(&mut file, &mut buffer, retries);
+    }
+
     // Now measure reads
+    let mut phases = Phases::new(config);
     let stopwatch = Instant::now();
 
     // this may not touch every block, but that's ok
     let count = size/u64::try_from(block_size).unwrap();
+    let mut coverage = Coverage::new(count);
     for i in 
         (0..count)
-            .map(|_| (&mut prng).next().unwrap() % count)
+            .map(|_| prng.next().unwrap() % count)
             .map(|i| i*u64::try_from(block_size).unwrap())
     {
+        coverage.touch(i/u64::try_from(block_size).unwrap());
         let step_size = usize::try_from(
             min(i+u64::try_from(block_size).unwrap(), size) - i
         ).unwrap();
         
         hint::black_box({
-            let mut file = OpenOptions::new()
-                .read(true)
-                .open(&path).unwrap();
-            file.seek(SeekFrom::Start(i)).unwrap();
-            file.read_exact(hint::black_box(&mut buffer[..step_size])).unwrap();
+            let mut file = phases.time(Phase::Open, || with_retry(retries, || {
+                OpenOptions::new()
+                    .read(true)
+                    .open(&path)
+            })).unwrap();
+            phases.time(Phase::Seek, || file.seek(SeekFrom::Start(i))).unwrap();
+            phases.time(Phase::Io, || with_retry(retries, || file.read_exact(hint::black_box(&mut buffer[..step_size])))).unwrap();
             &buffer
         });
     }
@@ -545,8 +634,105 @@ pub fn read_random(size: u64, block_size: usize, run: u32) -> Duration {
     // Truncate the file! Otherwise Veracruz may try to copy it back over
     // into the user's fs, which is a waste of (significant) time...
     //
-    let file = File::create(&path).unwrap();
+    let file = with_retry(retries, || File::create(&path)).unwrap();
+    file.set_len(0).unwrap();
+
+    Report::new(duration)
+        .with_phases(&phases)
+        .with_coverage(&coverage)
+        .with("warmup", warmup)
+}
+
+/// Append a large file's worth of blocks over --sessions sessions, each
+/// opening the file for appending, writing its share of the blocks,
+/// flushing and closing it again
+///
+/// This is a log reopened across program restarts. The whole sequence is
+/// timed, and each session's duration is reported too, to show whether
+/// reopening gets slower as the file grows.
+pub fn session_append(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, sessions, .. } = *config;
+    let path = format!("{}/session_append_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let mut prng = Prng::new(config.prng, seed);
+    let mut buffer = vec![0u8; block_size];
+
+    // start from an empty file, sessions only ever append
+    with_retry(retries, || File::create(&path)).unwrap();
+
+    let count = size.div_ceil(u64::try_from(block_size).unwrap());
+    let mut session_durations = Vec::with_capacity(usize::try_from(sessions).unwrap());
+
+    let stopwatch = Instant::now();
+
+    for session in 0..sessions {
+        let session_stopwatch = Instant::now();
+
+        hint::black_box({
+            let mut file = with_retry(retries, || {
+                OpenOptions::new()
+                    .append(true)
+                    .open(&path)
+            }).unwrap();
+
+            for block in session*count/sessions..(session+1)*count/sessions {
+                let i = block*u64::try_from(block_size).unwrap();
+                let step_size = usize::try_from(
+                    min(i+u64::try_from(block_size).unwrap(), size) - i
+                ).unwrap();
+                for (j, x) in
+                    (&mut prng)
+                        .take(step_size)
+                        .enumerate()
+                {
+                    buffer[j] = x as u8;
+                }
+                mark(config, &mut buffer);
+
+                let input = hint::black_box(&buffer[..step_size]);
+                with_retry(retries, || file.write_all(input)).unwrap();
+            }
+
+            file.flush().unwrap();
+        });
+
+        session_durations.push(session_stopwatch.elapsed());
+    }
+
+    let duration = stopwatch.elapsed();
+
+    let metadata = fs::metadata(&path).unwrap();
+
+    // every session's appends must have landed after the previous ones
+    let size_ok = metadata.len() == size;
+
+    // Truncate the file! Otherwise Veracruz may try to copy it back over
+    // into the user's fs, which is a waste of (significant) time...
+    //
+    let file = with_retry(retries, || File::create(&path)).unwrap();
     file.set_len(0).unwrap();
 
-    duration
+    Report::new(duration)
+        .with("sessions", sessions)
+        .with("session_runtimes", session_durations.iter()
+            .map(|duration| duration.as_secs_f64())
+            .collect::<Vec<_>>())
+        .with_latencies("session", &mut session_durations)
+        .with_footprint(&metadata)
+        .with("size_ok", size_ok)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::assert_partial_block;
+
+    #[test]
+    fn write_inorder_partial_block() {
+        assert_partial_block("incremental_write_inorder", write_inorder);
+    }
+
+    #[test]
+    fn write_reversed_partial_block() {
+        assert_partial_block("incremental_write_reversed", write_reversed);
+    }
 }
@@ -0,0 +1,39 @@
+//! Stopping a run early on Ctrl-C, keeping partial results
+//!
+//! ## Authors
+//!
+//! The Veracruz Development Team.
+//!
+//! ## Copyright
+//!
+//! See the file `LICENSING.markdown` in the Veracruz root directory for licensing
+//! and copyright information.
+
+use std::sync::atomic::{
+    AtomicBool,
+    Ordering,
+};
+
+/// Set once SIGINT has been received
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Install a SIGINT handler which asks the running benchmark to stop, so
+/// its partial results can still be written
+///
+/// A second SIGINT exits immediately, in case the benchmark doesn't check
+/// for interruption. Without the ctrlc feature this does nothing, and
+/// SIGINT kills the process as usual.
+pub fn install() {
+    #[cfg(all(unix, feature = "ctrlc"))]
+    ctrlc::set_handler(|| {
+        if INTERRUPTED.swap(true, Ordering::Relaxed) {
+            std::process::exit(130);
+        }
+    }).unwrap();
+}
+
+/// Whether the benchmark has been asked to stop, timed loops check this
+/// between blocks
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::Relaxed)
+}
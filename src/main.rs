@@ -9,45 +9,852 @@
 //! See the file `LICENSING.markdown` in the Veracruz root directory for licensing
 //! and copyright information.
 
-// black_box disable optimizations that depend on its value
-//
-// unfortunately it is only available on nightly
-//
-#![feature(test)]
-
-#[allow(unused)]
-use anyhow;
+// black_box is used on unit-valued blocks to keep the I/O inside them from
+// being optimized out, which clippy would otherwise flag
+#![allow(clippy::unit_arg)]
+
+use anyhow::{
+    bail,
+    Context,
+};
+use serde::Serialize;
+use serde_json::{
+    Map,
+    Value,
+};
 use std::{
+    convert::TryFrom,
     env,
+    env::VarError,
     fs,
+    fs::OpenOptions,
+    io,
+    io::Write,
+    str::FromStr,
+    time::Duration,
 };
 
 mod file;
+mod block_timings;
+mod phases;
+mod sequence;
 mod buffered_file;
 mod incremental_file;
 mod small_files;
+mod handles;
+mod rename;
+mod paths;
+mod report;
+mod retry;
+mod short_read;
+mod rng;
+mod interrupt;
+mod threads;
+mod hint;
+mod marker;
+mod think;
+mod repeat;
+mod prom;
+mod crc;
+mod affinity;
+mod units;
+mod scratch;
+mod size_dist;
+mod sync_mode;
+mod coverage;
+
+/// Default capacity of buffered readers, matching std's BufReader
+const DEFAULT_BUF_CAPACITY: usize = 8*1024;
+
+/// Seed of the pseudo-random data and access orders, unless overridden
+const DEFAULT_SEED: u64 = 42;
+
+/// Benchmark configuration, parsed from the command line
+///
+/// Options without a value on the command line take their default from a
+/// `VFS_BENCH_*` environment variable if it is set, so the precedence is
+/// command line > environment > built-in default. The environment variables
+/// are VFS_BENCH_SEED, VFS_BENCH_PRNG, VFS_BENCH_RETRIES, VFS_BENCH_WARMUP
+/// (0/1 or false/true), VFS_BENCH_BUF_CAPACITY, VFS_BENCH_SETUP_THREADS,
+/// VFS_BENCH_THREADS and VFS_BENCH_RESULTS_DIR.
+///
+/// There is no VFS_BENCH_ITERATIONS, each invocation measures a single run
+/// and --repeat-until is the way to repeat it, so setting it is an error
+/// rather than being silently ignored.
+#[derive(Clone)]
+pub struct Config {
+    pub mode: String,
+    pub size: u64,
+    pub block_size: usize,
+    pub run: u32,
+    /// seed of the pseudo-random data and access orders, must be non-zero
+    /// for xorshift64, with --seed-per-run this is already derived from the
+    /// run
+    pub seed: u64,
+    /// generator of the pseudo-random data and access orders
+    pub prng: rng::Algorithm,
+    /// number of times to retry an interrupted open/read/write
+    pub retries: u32,
+    /// read the whole file sequentially before random-read measurements
+    pub warmup: bool,
+    /// capacity of the BufReader used by the buffered read modes
+    pub buf_capacity: usize,
+    /// also print each result as a line of JSON on stdout
+    pub stdout_jsonl: bool,
+    /// append each result as a line of JSON to this file, instead of
+    /// writing one file per result
+    pub ndjson: Option<String>,
+    /// number of threads used to create the files of the small-file modes,
+    /// only the untimed setup is parallelized
+    pub setup_threads: u32,
+    /// number of threads in the modes that measure concurrency
+    pub threads: u32,
+    /// check file contents between operations, in modes that support it
+    pub verify: bool,
+    /// write the duration of each block operation to this CSV file, in
+    /// modes that support it
+    pub block_csv: Option<String>,
+    /// fill written data with this repeating string instead of random bytes
+    pub marker: Option<String>,
+    /// number of blocks read sequentially after each seek in seek_then_scan
+    pub scan_blocks: u64,
+    /// number of times each block is read before moving on, in the plain
+    /// read modes
+    pub reuse: u32,
+    /// number of microseconds to pause between operations, outside the
+    /// timed region, in the plain file modes
+    pub think_us: u64,
+    /// stop after this many timed operations even if the file has more
+    /// blocks, in the plain file modes
+    pub max_ops: Option<u64>,
+    /// number of files in each directory in mkdir_then_write, copy_tree and
+    /// tree_size
+    pub files_per_dir: u64,
+    /// run the benchmark repeatedly until its measured runtimes add up to
+    /// this many seconds
+    pub repeat_until: Option<f64>,
+    /// size in bytes of the records written by write_records
+    pub record_size: usize,
+    /// size in bytes of each read_exact call read_chunks splits a block
+    /// into
+    pub read_chunk: usize,
+    /// number of other files opened between the two opens of the target in
+    /// metadata_eviction
+    pub pollute: u64,
+    /// size in bytes at which log_rotate rotates its log
+    pub rotate_bytes: u64,
+    /// format of the results written to results_dir, unless --ndjson is given
+    pub format: prom::Format,
+    /// store a CRC32 in the first 4 bytes of each written block, and verify
+    /// it after reading, in write_inorder and read_inorder, other modes
+    /// reject it
+    pub inline_crc: bool,
+    /// pin the benchmark to this CPU before running it
+    pub pin_cpu: Option<usize>,
+    /// number of files kept in the directory by steady_population
+    pub population: u64,
+    /// unit of the runtime field of the results
+    pub time_unit: units::TimeUnit,
+    /// fraction of each file read by partial_read
+    pub read_fraction: f64,
+    /// run the benchmark once per seed for this many consecutive seeds,
+    /// starting from --seed
+    pub seed_sweep: Option<u32>,
+    /// remove a run's directory left over from an earlier run, instead of
+    /// refusing to reuse it
+    pub fresh: bool,
+    /// distribution of file sizes in small_write_inorder and
+    /// small_read_inorder, with a mean of block_size
+    pub size_dist: size_dist::SizeDist,
+    /// number of open-append-close sessions session_append splits the
+    /// file's blocks over
+    pub sessions: u64,
+    /// write the block indices visited during the timed phase to this
+    /// file, in modes that support it
+    pub dump_sequence: Option<String>,
+    /// block indices and operations replay runs, loaded from --trace
+    pub trace: Option<Vec<(u64, sequence::Op)>>,
+    /// directory results are written to, created if missing
+    pub results_dir: String,
+    /// directory the benchmarks create their files under, always /scratch
+    /// as mapped in by Veracruz, except in the tests
+    pub scratch_dir: String,
+    /// length in characters of the longest names unicode_names creates
+    pub name_len: usize,
+    /// pretty-print the JSON written to results_dir and stdout, NDJSON
+    /// stays one line per result
+    pub json_pretty: bool,
+    /// how long rw_interference keeps its reader and writer running, in
+    /// seconds
+    pub duration: f64,
+    /// time the open, seek, I/O and flush of each block separately in the
+    /// incremental modes, reported as phase_seconds, other modes reject it
+    pub phase_timings: bool,
+    /// how the plain file write and update modes, and update_shuffled,
+    /// sync the file after their last write, other modes reject anything
+    /// but none
+    pub sync_mode: sync_mode::SyncMode,
+    /// number of passes read_shuffled and update_shuffled make over their
+    /// one shuffled order
+    pub epochs: u64,
+}
+
+impl Config {
+    /// Parse `<mode> <size> <block_size> [run]` followed by any options,
+    /// with defaults for the options taken from the environment
+    fn from_env_and_args(args: &[String]) -> anyhow::Result<Config> {
+        if env::var_os("VFS_BENCH_ITERATIONS").is_some() {
+            bail!("VFS_BENCH_ITERATIONS isn't supported, each invocation measures one run, \
+                use --repeat-until to repeat it");
+        }
+
+        let mut positional = Vec::new();
+        let mut seed = env_default("VFS_BENCH_SEED")?
+            .unwrap_or(DEFAULT_SEED);
+        let mut prng = env_default("VFS_BENCH_PRNG")?
+            .unwrap_or(rng::Algorithm::Xorshift64);
+        let mut retries = env_default("VFS_BENCH_RETRIES")?
+            .unwrap_or(3);
+        let mut warmup = env_flag("VFS_BENCH_WARMUP")?
+            .unwrap_or(false);
+        let mut buf_capacity = env_default("VFS_BENCH_BUF_CAPACITY")?
+            .unwrap_or(DEFAULT_BUF_CAPACITY);
+        let mut stdout_jsonl = false;
+        let mut ndjson = None;
+        let mut setup_threads = env_default("VFS_BENCH_SETUP_THREADS")?
+            .unwrap_or(1);
+        let mut threads = env_default("VFS_BENCH_THREADS")?
+            .unwrap_or(1);
+        let mut verify = false;
+        let mut block_csv = None;
+        let mut marker = None;
+        let mut scan_blocks = 8;
+        let mut reuse = 1;
+        let mut think_us = 0;
+        let mut max_ops = None;
+        let mut files_per_dir = 16;
+        let mut repeat_until = None;
+        let mut record_size = 64;
+        let mut read_chunk = 512;
+        let mut pollute = 1024;
+        let mut rotate_bytes = 1024*1024;
+        let mut format = prom::Format::Json;
+        let mut inline_crc = false;
+        let mut pin_cpu = None;
+        let mut population = 64;
+        let mut time_unit = units::TimeUnit::S;
+        let mut read_fraction = 0.25;
+        let mut seed_sweep = None;
+        let mut fresh = false;
+        let mut seed_per_run = false;
+        let mut size_dist = size_dist::SizeDist::Fixed;
+        let mut sessions = 8;
+        let mut dump_sequence = None;
+        let mut trace_path = None;
+        let mut name_len = 64;
+        let mut json_pretty = false;
+        let mut duration = 1.0;
+        let mut phase_timings = false;
+        let mut sync_mode = sync_mode::SyncMode::None;
+        let mut epochs = 4;
+        let mut results_dir = env_default("VFS_BENCH_RESULTS_DIR")?
+            .unwrap_or_else(|| "/results".to_owned());
+
+        let mut args = args.iter().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_ref() {
+                "--seed" => {
+                    seed = args.next()
+                        .context("Missing value for --seed")?
+                        .parse::<u64>()
+                        .context("Can't parse --seed")?;
+                }
+                "--prng" => {
+                    prng = args.next()
+                        .context("Missing value for --prng")?
+                        .parse::<rng::Algorithm>()
+                        .context("Can't parse --prng")?;
+                }
+                "--retries" => {
+                    retries = args.next()
+                        .context("Missing value for --retries")?
+                        .parse::<u32>()
+                        .context("Can't parse --retries")?;
+                }
+                "--warmup" => warmup = true,
+                "--buf-capacity" => {
+                    buf_capacity = args.next()
+                        .context("Missing value for --buf-capacity")?
+                        .parse::<usize>()
+                        .context("Can't parse --buf-capacity")?;
+                }
+                "--stdout-jsonl" => stdout_jsonl = true,
+                "--ndjson" => {
+                    ndjson = Some(
+                        args.next()
+                            .context("Missing value for --ndjson")?
+                            .clone()
+                    );
+                }
+                "--setup-threads" => {
+                    setup_threads = args.next()
+                        .context("Missing value for --setup-threads")?
+                        .parse::<u32>()
+                        .context("Can't parse --setup-threads")?;
+                }
+                "--threads" => {
+                    threads = args.next()
+                        .context("Missing value for --threads")?
+                        .parse::<u32>()
+                        .context("Can't parse --threads")?;
+                }
+                "--verify" => verify = true,
+                "--block-csv" => {
+                    block_csv = Some(
+                        args.next()
+                            .context("Missing value for --block-csv")?
+                            .clone()
+                    );
+                }
+                "--marker" => {
+                    let value = args.next()
+                        .context("Missing value for --marker")?;
+                    if value.is_empty() {
+                        bail!("--marker must not be empty");
+                    }
+                    marker = Some(value.clone());
+                }
+                "--scan-blocks" => {
+                    scan_blocks = args.next()
+                        .context("Missing value for --scan-blocks")?
+                        .parse::<u64>()
+                        .context("Can't parse --scan-blocks")?;
+                    if scan_blocks == 0 {
+                        bail!("--scan-blocks must be at least 1");
+                    }
+                }
+                "--reuse" => {
+                    reuse = args.next()
+                        .context("Missing value for --reuse")?
+                        .parse::<u32>()
+                        .context("Can't parse --reuse")?;
+                    if reuse == 0 {
+                        bail!("--reuse must be at least 1");
+                    }
+                }
+                "--think-us" => {
+                    think_us = args.next()
+                        .context("Missing value for --think-us")?
+                        .parse::<u64>()
+                        .context("Can't parse --think-us")?;
+                }
+                "--max-ops" => {
+                    max_ops = Some(
+                        args.next()
+                            .context("Missing value for --max-ops")?
+                            .parse::<u64>()
+                            .context("Can't parse --max-ops")?
+                    );
+                }
+                "--files-per-dir" => {
+                    files_per_dir = args.next()
+                        .context("Missing value for --files-per-dir")?
+                        .parse::<u64>()
+                        .context("Can't parse --files-per-dir")?;
+                    if files_per_dir == 0 {
+                        bail!("--files-per-dir must be at least 1");
+                    }
+                }
+                "--repeat-until" => {
+                    let seconds = args.next()
+                        .context("Missing value for --repeat-until")?
+                        .parse::<f64>()
+                        .context("Can't parse --repeat-until")?;
+                    if !(seconds > 0.0 && seconds.is_finite()) {
+                        bail!("--repeat-until must be a positive number of seconds");
+                    }
+                    repeat_until = Some(seconds);
+                }
+                "--record-size" => {
+                    record_size = args.next()
+                        .context("Missing value for --record-size")?
+                        .parse::<usize>()
+                        .context("Can't parse --record-size")?;
+                    if record_size == 0 {
+                        bail!("--record-size must be at least 1");
+                    }
+                }
+                "--read-chunk" => {
+                    read_chunk = args.next()
+                        .context("Missing value for --read-chunk")?
+                        .parse::<usize>()
+                        .context("Can't parse --read-chunk")?;
+                    if read_chunk == 0 {
+                        bail!("--read-chunk must be at least 1");
+                    }
+                }
+                "--pollute" => {
+                    pollute = args.next()
+                        .context("Missing value for --pollute")?
+                        .parse::<u64>()
+                        .context("Can't parse --pollute")?;
+                }
+                "--rotate-bytes" => {
+                    rotate_bytes = args.next()
+                        .context("Missing value for --rotate-bytes")?
+                        .parse::<u64>()
+                        .context("Can't parse --rotate-bytes")?;
+                    if rotate_bytes == 0 {
+                        bail!("--rotate-bytes must be at least 1");
+                    }
+                }
+                "--format" => {
+                    format = args.next()
+                        .context("Missing value for --format")?
+                        .parse::<prom::Format>()
+                        .context("Can't parse --format")?;
+                }
+                "--inline-crc" => inline_crc = true,
+                "--pin-cpu" => {
+                    pin_cpu = Some(
+                        args.next()
+                            .context("Missing value for --pin-cpu")?
+                            .parse::<usize>()
+                            .context("Can't parse --pin-cpu")?
+                    );
+                }
+                "--population" => {
+                    population = args.next()
+                        .context("Missing value for --population")?
+                        .parse::<u64>()
+                        .context("Can't parse --population")?;
+                }
+                "--time-unit" => {
+                    time_unit = args.next()
+                        .context("Missing value for --time-unit")?
+                        .parse::<units::TimeUnit>()
+                        .context("Can't parse --time-unit")?;
+                }
+                "--read-fraction" => {
+                    read_fraction = args.next()
+                        .context("Missing value for --read-fraction")?
+                        .parse::<f64>()
+                        .context("Can't parse --read-fraction")?;
+                    if !(read_fraction > 0.0 && read_fraction <= 1.0) {
+                        bail!("--read-fraction must be in (0, 1]");
+                    }
+                }
+                "--seed-sweep" => {
+                    let n = args.next()
+                        .context("Missing value for --seed-sweep")?
+                        .parse::<u32>()
+                        .context("Can't parse --seed-sweep")?;
+                    if n == 0 {
+                        bail!("--seed-sweep must be at least 1");
+                    }
+                    seed_sweep = Some(n);
+                }
+                "--fresh" => fresh = true,
+                "--seed-per-run" => seed_per_run = true,
+                "--size-dist" => {
+                    size_dist = args.next()
+                        .context("Missing value for --size-dist")?
+                        .parse::<size_dist::SizeDist>()
+                        .context("Can't parse --size-dist")?;
+                }
+                "--sessions" => {
+                    sessions = args.next()
+                        .context("Missing value for --sessions")?
+                        .parse::<u64>()
+                        .context("Can't parse --sessions")?;
+                    if sessions == 0 {
+                        bail!("--sessions must be at least 1");
+                    }
+                }
+                "--dump-sequence" => {
+                    dump_sequence = Some(
+                        args.next()
+                            .context("Missing value for --dump-sequence")?
+                            .clone()
+                    );
+                }
+                "--results-dir" => {
+                    results_dir = args.next()
+                        .context("Missing value for --results-dir")?
+                        .clone();
+                }
+                "--name-len" => {
+                    name_len = args.next()
+                        .context("Missing value for --name-len")?
+                        .parse::<usize>()
+                        .context("Can't parse --name-len")?;
+                    // names need 4 characters to stay unique for millions
+                    // of files, and at 3 bytes each must fit in 255 bytes
+                    if !(4..=85).contains(&name_len) {
+                        bail!("--name-len must be between 4 and 85");
+                    }
+                }
+                "--json-pretty" => json_pretty = true,
+                "--phase-timings" => phase_timings = true,
+                "--epochs" => {
+                    epochs = args.next()
+                        .context("Missing value for --epochs")?
+                        .parse::<u64>()
+                        .context("Can't parse --epochs")?;
+                    if epochs == 0 {
+                        bail!("--epochs must be at least 1");
+                    }
+                }
+                "--sync-mode" => {
+                    sync_mode = args.next()
+                        .context("Missing value for --sync-mode")?
+                        .parse::<sync_mode::SyncMode>()
+                        .context("Can't parse --sync-mode")?;
+                }
+                "--duration" => {
+                    duration = args.next()
+                        .context("Missing value for --duration")?
+                        .parse::<f64>()
+                        .context("Can't parse --duration")?;
+                    if !(duration > 0.0 && duration.is_finite()) {
+                        bail!("--duration must be a positive number of seconds");
+                    }
+                }
+                "--trace" => {
+                    trace_path = Some(
+                        args.next()
+                            .context("Missing value for --trace")?
+                            .clone()
+                    );
+                }
+                _ if arg.starts_with("--") => bail!("Unknown option {:?}", arg),
+                _ => positional.push(arg),
+            }
+        }
+
+        if positional.len() < 3 || positional.len() > 4 {
+            bail!("Wrong number of arguments");
+        }
+
+        // these may come from either the environment or the command line
+        if seed == 0 && prng == rng::Algorithm::Xorshift64 {
+            bail!("seed must be non-zero, xorshift64 would only produce zeros");
+        }
+        if setup_threads == 0 {
+            bail!("setup threads must be at least 1");
+        }
+        if threads == 0 {
+            bail!("threads must be at least 1");
+        }
+        if repeat_until.is_some() && seed_sweep.is_some() {
+            bail!("--repeat-until and --seed-sweep can't be combined");
+        }
+
+        // the other small file modes read whole block_size files
+        if size_dist != size_dist::SizeDist::Fixed
+            && !matches!(positional[0].as_str(), "small_write_inorder" | "small_read_inorder")
+        {
+            bail!("--size-dist is only supported by small_write_inorder and small_read_inorder");
+        }
+
+        // the other write modes have no single file to sync at the end
+        if sync_mode != sync_mode::SyncMode::None
+            && !matches!(positional[0].as_str(),
+                "write_inorder" | "update_inorder"
+                | "write_reversed" | "update_reversed"
+                | "write_random" | "update_random"
+                | "update_shuffled"
+            )
+        {
+            bail!("--sync-mode is only supported by write_inorder, update_inorder, write_reversed, \
+                update_reversed, write_random, update_random and update_shuffled");
+        }
+
+        // only the incremental modes open and seek separately for each block
+        if phase_timings && !positional[0].starts_with("incremental_") {
+            bail!("--phase-timings is only supported by the incremental_* modes");
+        }
+
+        // only these check what they wrote or read against the CRCs
+        if inline_crc && !matches!(positional[0].as_str(), "write_inorder" | "read_inorder") {
+            bail!("--inline-crc is only supported by write_inorder and read_inorder");
+        }
+
+        let run = match positional.get(3) {
+            Some(run) => run.parse::<u32>().context("Can't parse run")?,
+            None => 0,
+        };
+
+        // give each run its own data and access orders, still
+        // reproducible since the seed is reported with the results
+        if seed_per_run {
+            seed = rng::seed_for_run(seed, run);
+        }
+
+        let size = positional[1].parse::<u64>()
+            .context("Can't parse size")?;
+        let block_size = positional[2].parse::<usize>()
+            .context("Can't parse block_size")?;
+
+        // refuse a buffer too large for write_oneshot before doing anything
+        if positional[0] == "write_oneshot" && size > file::ONESHOT_LIMIT {
+            bail!("write_oneshot needs a single {} byte buffer, limit is {} bytes",
+                size, file::ONESHOT_LIMIT);
+        }
+        if positional[0] == "read_chunks" && read_chunk > block_size {
+            bail!("read_chunks needs a --read-chunk of at most block_size ({} bytes), got {}",
+                block_size, read_chunk);
+        }
+        if positional[0] == "seek_then_scan" && scan_blocks > size/u64::try_from(block_size).unwrap() {
+            bail!("seek_then_scan needs at least --scan-blocks ({}) blocks, file only has {}",
+                scan_blocks, size/u64::try_from(block_size).unwrap());
+        }
+        if positional[0] == "write_records" && u64::try_from(record_size).unwrap() > size {
+            bail!("write_records needs a --record-size of at most size ({} bytes), got {}",
+                size, record_size);
+        }
+        if positional[0] == "clone_offsets" && size < 2*u64::try_from(block_size).unwrap() {
+            bail!("clone_offsets needs at least two blocks, size must be at least {} bytes",
+                2*block_size);
+        }
+        if positional[0] == "concurrent_append" && block_size < 8 {
+            bail!("concurrent_append needs records of at least 8 bytes, got a block_size of {}",
+                block_size);
+        }
+
+        // load the trace up front, so a malformed one is reported like any
+        // other bad argument rather than part way through a benchmark
+        let trace = match (positional[0].as_str(), trace_path) {
+            ("replay", Some(path)) => Some(sequence::load(
+                &path, size.div_ceil(u64::try_from(block_size).unwrap())
+            )?),
+            ("replay", None) => bail!("replay needs a --trace to replay"),
+            (_, Some(_)) => bail!("--trace is only used by replay"),
+            (_, None) => None,
+        };
+
+        Ok(Config {
+            mode: positional[0].clone(),
+            size,
+            block_size,
+            run,
+            seed,
+            prng,
+            retries,
+            warmup,
+            buf_capacity,
+            stdout_jsonl,
+            ndjson,
+            setup_threads,
+            threads,
+            verify,
+            block_csv,
+            marker,
+            scan_blocks,
+            reuse,
+            think_us,
+            max_ops,
+            files_per_dir,
+            repeat_until,
+            record_size,
+            read_chunk,
+            pollute,
+            rotate_bytes,
+            format,
+            inline_crc,
+            pin_cpu,
+            population,
+            time_unit,
+            read_fraction,
+            seed_sweep,
+            fresh,
+            size_dist,
+            sessions,
+            dump_sequence,
+            trace,
+            results_dir,
+            scratch_dir: String::from("/scratch"),
+            name_len,
+            json_pretty,
+            duration,
+            phase_timings,
+            sync_mode,
+            epochs,
+        })
+    }
+
+    /// Whether a timed loop has already done --max-ops operations
+    pub fn capped(&self, ops: u64) -> bool {
+        self.max_ops.is_some_and(|max_ops| ops >= max_ops)
+    }
+}
+
+/// Read the default of an option from an environment variable, if it is set
+fn env_default<T>(name: &str) -> anyhow::Result<Option<T>>
+where
+    T: FromStr,
+    T::Err: Into<anyhow::Error>,
+{
+    match env::var(name) {
+        Ok(value) => Ok(Some(
+            value.parse::<T>()
+                .map_err(Into::into)
+                .with_context(|| format!("Can't parse {}", name))?
+        )),
+        Err(VarError::NotPresent) => Ok(None),
+        Err(err) => Err(err).with_context(|| format!("Can't read {}", name)),
+    }
+}
+
+/// Read the default of a flag from an environment variable, if it is set,
+/// accepting 0/1 as well as false/true
+fn env_flag(name: &str) -> anyhow::Result<Option<bool>> {
+    match env::var(name) {
+        Ok(value) => match value.as_ref() {
+            "1" | "true" => Ok(Some(true)),
+            "0" | "false" => Ok(Some(false)),
+            _ => bail!("Can't parse {}, expected 0/1 or false/true", name),
+        },
+        Err(VarError::NotPresent) => Ok(None),
+        Err(err) => Err(err).with_context(|| format!("Can't read {}", name)),
+    }
+}
+
+/// Version of the result format below, this must be bumped whenever fields
+/// are added or their meaning changes, so downstream tooling can tell which
+/// shape to expect
+const SCHEMA_VERSION: u32 = 25;
+
+/// Format of the result file written for each run
+#[derive(Serialize)]
+struct Output<'a> {
+    schema_version: u32,
+    name: &'a str,
+    size: u64,
+    block_size: usize,
+    run: u32,
+    seed: u64,
+    prng: &'a str,
+    think_us: u64,
+    max_ops: Option<u64>,
+    black_box: bool,
+    supported: bool,
+    interrupted: bool,
+    runtime: f64,
+    time_unit: units::TimeUnit,
+    retries: u64,
+    sync_mode: sync_mode::SyncMode,
+    /// the command line the result came from, so it can be reproduced
+    argv: String,
+    /// block operations per second, for the fixed-block modes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    iops: Option<f64>,
+    /// block operations a full run does, for the fixed-block modes
+    #[serde(skip)]
+    block_ops: Option<u64>,
+    #[serde(flatten)]
+    extra: &'a Map<String, Value>,
+}
+
+impl Output<'_> {
+    /// Number of block operations measured, the number a full run does, or
+    /// the number actually completed if the run was cut short
+    ///
+    /// Modes that aren't fixed-block count one per block, including a
+    /// trailing partial block.
+    fn ops(&self) -> u64 {
+        self.extra.get("blocks_completed")
+            .and_then(|blocks| blocks.as_u64())
+            .or(self.block_ops)
+            .unwrap_or_else(|| self.size.div_ceil(u64::try_from(self.block_size).unwrap()))
+    }
+}
+
+/// Number of block operations a full run of a mode does, for the modes doing
+/// one operation per block_size block so their IOPS are meaningful
+fn block_ops(mode: &str, config: &Config) -> Option<u64> {
+    let block_size = u64::try_from(config.block_size).unwrap();
+    match mode {
+        // every block, a trailing partial block included
+        "write_inorder" | "update_inorder" | "read_inorder"
+        | "write_reversed" | "update_reversed" | "read_reversed"
+        | "buffered_write_inorder" | "buffered_update_inorder" | "buffered_read_inorder"
+        | "buffered_write_reversed" | "buffered_update_reversed" | "buffered_read_reversed"
+        | "incremental_write_inorder" | "incremental_update_inorder" | "incremental_read_inorder"
+        | "incremental_write_reversed" | "incremental_update_reversed" | "incremental_read_reversed"
+            => Some(config.size.div_ceil(block_size)),
+        // every block once per epoch
+        "read_shuffled" | "update_shuffled"
+            => Some(config.size.div_ceil(block_size) * config.epochs),
+        // only whole blocks, either as files or as operations that must fit
+        // in the file
+        "write_random" | "update_random" | "read_random"
+        | "write_unaligned" | "read_unaligned"
+        | "buffered_write_random" | "buffered_update_random" | "buffered_read_random"
+        | "buffered_read_seek_random"
+        | "incremental_write_random" | "incremental_update_random" | "incremental_read_random"
+        | "small_write_inorder" | "small_update_inorder" | "small_read_inorder"
+        | "small_write_reversed" | "small_update_reversed" | "small_read_reversed"
+        | "small_write_random" | "small_update_random" | "small_read_random"
+            => Some(config.size / block_size),
+        _ => None,
+    }
+}
 
 
 /// entry point
 fn main() {
     // parse arguments
     let args = env::args().collect::<Vec<_>>();
-    if args.len() < 4 || args.len() > 5 {
-        eprintln!("./{} <mode> <size> [block_size] [run]", args[0]);
-        return;
-    }
+    let config = match Config::from_env_and_args(&args) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{}", err);
+            eprintln!("./{} <mode> <size> [block_size] [run] [--seed <n>] [--prng <xorshift64|pcg|splitmix64>] [--retries <n>] [--warmup] [--buf-capacity <n>] [--stdout-jsonl] [--ndjson <path>] [--setup-threads <n>] [--threads <n>] [--verify] [--block-csv <path>] [--marker <string>] [--scan-blocks <n>] [--reuse <n>] [--think-us <n>] [--max-ops <n>] [--files-per-dir <n>] [--repeat-until <seconds>] [--record-size <n>] [--read-chunk <n>] [--pollute <n>] [--rotate-bytes <n>] [--format <json|prom>] [--inline-crc] [--pin-cpu <n>] [--population <n>] [--time-unit <s|ms|us|ns>] [--read-fraction <f>] [--seed-sweep <n>] [--fresh] [--seed-per-run] [--size-dist <fixed|uniform|lognormal>] [--sessions <n>] [--dump-sequence <path>] [--trace <path>] [--results-dir <path>] [--name-len <n>] [--json-pretty] [--duration <seconds>] [--phase-timings] [--sync-mode <none|data|full>] [--epochs <n>]", args[0]);
+            return;
+        }
+    };
 
-    let mode = &args[1];
-    let benchmark = match args[1].as_ref() {
+    let mode = &config.mode;
+    let benchmark = match mode.as_ref() {
         "write_inorder"                 => file::write_inorder,
+        "write_single"                  => file::write_single,
+        "byte_append"                   => file::byte_append,
         "update_inorder"                => file::update_inorder,
         "read_inorder"                  => file::read_inorder,
+        "read_chunks"                   => file::read_chunks,
         "write_reversed"                => file::write_reversed,
         "update_reversed"               => file::update_reversed,
         "read_reversed"                 => file::read_reversed,
         "write_random"                  => file::write_random,
         "update_random"                 => file::update_random,
         "read_random"                   => file::read_random,
+        "read_shuffled"                 => file::read_shuffled,
+        "update_shuffled"               => file::update_shuffled,
+        "replay"                        => file::replay,
+        "read_from_end"                 => file::read_from_end,
+        "write_oneshot"                 => file::write_oneshot,
+        "write_sync_data"               => file::write_sync_data,
+        "durable_roundtrip"             => file::durable_roundtrip,
+        "read_past_eof"                 => file::read_past_eof,
+        "read_after_truncate"           => file::read_after_truncate,
+        "open_truncate"                 => file::open_truncate,
+        "truncate_shrink"               => file::truncate_shrink,
+        "hotspot_write"                 => file::hotspot_write,
+        "write_first"                   => file::write_first,
+        "write_unaligned"               => file::write_unaligned,
+        "write_ramp"                    => file::write_ramp,
+        "write_records"                 => file::write_records,
+        "read_unaligned"                => file::read_unaligned,
+        "locality_compare"              => file::locality_compare,
+        "read_ro_vs_rw"                 => file::read_ro_vs_rw,
+        "read_bytes_iter"               => file::read_bytes_iter,
+        "tail_read"                     => file::tail_read,
+        "length_poll"                   => file::length_poll,
+        "concurrent_append"             => file::concurrent_append,
+        "rw_interference"               => file::rw_interference,
+        "io_copy"                       => file::io_copy,
+        "seek_then_scan"                => file::seek_then_scan,
         "buffered_write_inorder"        => buffered_file::write_inorder,
         "buffered_update_inorder"       => buffered_file::update_inorder,
         "buffered_read_inorder"         => buffered_file::read_inorder,
@@ -57,6 +864,9 @@ fn main() {
         "buffered_write_random"         => buffered_file::write_random,
         "buffered_update_random"        => buffered_file::update_random,
         "buffered_read_random"          => buffered_file::read_random,
+        "buffered_read_seek_inorder"    => buffered_file::read_seek_inorder,
+        "buffered_read_seek_random"     => buffered_file::read_seek_random,
+        "read_buffered_compare"         => buffered_file::read_buffered_compare,
         "incremental_write_inorder"     => incremental_file::write_inorder,
         "incremental_update_inorder"    => incremental_file::update_inorder,
         "incremental_read_inorder"      => incremental_file::read_inorder,
@@ -66,8 +876,13 @@ fn main() {
         "incremental_write_random"      => incremental_file::write_random,
         "incremental_update_random"     => incremental_file::update_random,
         "incremental_read_random"       => incremental_file::read_random,
+        "session_append"                => incremental_file::session_append,
         "small_write_inorder"           => small_files::write_inorder,
         "small_read_inorder"            => small_files::read_inorder,
+        "partial_read"                  => small_files::partial_read,
+        "stat_then_read"                => small_files::stat_then_read,
+        "truncate_compare"              => small_files::truncate_compare,
+        "set_mtime"                     => small_files::set_mtime,
         "small_update_inorder"          => small_files::update_inorder,
         "small_write_reversed"          => small_files::write_reversed,
         "small_read_reversed"           => small_files::read_reversed,
@@ -75,68 +890,274 @@ fn main() {
         "small_write_random"            => small_files::write_random,
         "small_read_random"             => small_files::read_random,
         "small_update_random"           => small_files::update_random,
+        "small_rename_crossdir"         => small_files::rename_crossdir,
+        "small_fs_write_inorder"        => small_files::fs_write_inorder,
+        "small_fs_read_inorder"         => small_files::fs_read_inorder,
+        "small_create_contention"       => small_files::create_contention,
+        "small_create_new"              => small_files::create_new,
+        "unicode_names"                 => small_files::unicode_names,
+        "small_create_many"             => small_files::create_many,
+        "mkdir_then_write"              => small_files::mkdir_then_write,
+        "churn"                         => small_files::churn,
+        "steady_population"             => small_files::steady_population,
+        "copy_tree"                     => small_files::copy_tree,
+        "tree_size"                     => small_files::tree_size,
+        "list_churn"                    => small_files::list_churn,
+        "clone_offsets"                 => handles::clone_offsets,
+        "clone_handle"                  => handles::clone_handle,
+        "mixed_flags"                   => handles::mixed_flags,
+        "fd_pressure"                   => handles::fd_pressure,
+        "rename_replace"                => rename::rename_replace,
+        "rename_dir"                    => rename::rename_dir,
+        "log_rotate"                    => rename::log_rotate,
+        "path_depth"                    => paths::path_depth,
+        "open_missing"                  => paths::open_missing,
+        "canonicalize"                  => paths::canonicalize,
+        "metadata_eviction"             => paths::metadata_eviction,
+        "read_link"                     => paths::read_link,
         _ => {
             eprintln!("Unknown mode {:?}", mode);
             return;
         }
     };
 
-    let size = match args[2].parse::<u64>() {
-        Ok(size) => size,
-        Err(_) => {
-            eprintln!("Can't parse size");
-            return;
-        }
-    };
+    // with the ctrlc feature, Ctrl-C stops the benchmark early but still
+    // writes what it measured so far
+    interrupt::install();
+
+    if cfg!(feature = "no_black_box") {
+        eprintln!("warning: built without black_box, I/O may be optimized out, \
+            results are tagged black_box=false and are not valid I/O numbers");
+    }
 
-    let block_size = match args[3].parse::<usize>() {
-        Ok(block_size) => block_size,
-        Err(_) => {
-            eprintln!("Can't parse block_size");
+    // pin before anything is measured, failing loudly rather than quietly
+    // running unpinned
+    if let Some(cpu) = config.pin_cpu {
+        if let Err(err) = affinity::pin(cpu) {
+            eprintln!("Can't pin to CPU {}: {}", cpu, err);
             return;
         }
-    };
-
-    let run = match args.get(4) {
-        Some(run) => match run.parse::<u32>() {
-            Ok(run) => run,
-            Err(_) => {
-                eprintln!("Can't parse run");
-                return;
-            }
-        },
-        None => 0,
-    };
+    }
 
     // run benchmarks
     println!("benchmarking {}: size={}, block_size={}",
-        mode, size, block_size
+        mode, config.size, config.block_size
     );
 
-    let duration = benchmark(size, block_size, run);
+    let report = match (config.repeat_until, config.seed_sweep) {
+        (Some(seconds), _) => repeat::repeat_until(
+            &config, benchmark, Duration::from_secs_f64(seconds)
+        ),
+        (None, Some(n)) => repeat::seed_sweep(&config, benchmark, n),
+        (None, None) => benchmark(&config),
+    }.with_peak_rss();
+    let report = match config.pin_cpu {
+        Some(cpu) => report.with("pinned_cpu", cpu),
+        None => report,
+    };
 
     println!("benchmarking {}: runtime={:?}",
-        mode, duration
+        mode, report.runtime
     );
 
-    // write results to file
-    fs::write(
-        format!("/results/result_{}_{}_{}_{}.json",
-            mode, size, block_size, run
-        ),
-        format!(
-            "{{\
-                \"name\":{:?},\
-                \"size\":{},\
-                \"block_size\":{},\
-                \"run\":{},\
-                \"runtime\":{}\
-            }}",
-            mode,
-            size,
-            block_size,
-            run,
-            duration.as_secs_f64(),
-        )
-    ).unwrap();
+    let mut output = Output {
+        schema_version: SCHEMA_VERSION,
+        name: mode,
+        size: config.size,
+        block_size: config.block_size,
+        run: config.run,
+        seed: config.seed,
+        prng: config.prng.name(),
+        think_us: config.think_us,
+        max_ops: config.max_ops,
+        black_box: !cfg!(feature = "no_black_box"),
+        supported: report.supported,
+        interrupted: interrupt::interrupted(),
+        runtime: config.time_unit.scale(report.runtime),
+        time_unit: config.time_unit,
+        retries: retry::retries(),
+        sync_mode: config.sync_mode,
+        argv: args.join(" "),
+        iops: None,
+        block_ops: block_ops(mode, &config),
+        extra: &report.extra,
+    };
+    if output.block_ops.is_some() {
+        output.iops = Some(output.ops() as f64 / report.runtime.as_secs_f64());
+    }
+    let result = serde_json::to_string(&output).unwrap();
+    let readable = if config.json_pretty {
+        serde_json::to_string_pretty(&output).unwrap()
+    } else {
+        result.clone()
+    };
+
+    if let Some(ndjson) = &config.ndjson {
+        // append to a shared NDJSON file instead, each result goes out in a
+        // single write of a single line, so with O_APPEND concurrent runs
+        // appending to the same file don't interleave
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(ndjson).unwrap();
+        file.write_all(format!("{}\n", result).as_bytes()).unwrap();
+    } else {
+        // anything polling the results may read them at any moment, so
+        // write them aside and rename them into place
+        fs::create_dir_all(&config.results_dir).unwrap();
+        if config.format == prom::Format::Prom {
+            write_atomically(
+                &format!("{}/metrics_{}_{}_{}_{}.prom",
+                    config.results_dir, mode, config.size, config.block_size, config.run
+                ),
+                prom::render(&output)
+            ).unwrap();
+        } else {
+            write_atomically(
+                &format!("{}/result_{}_{}_{}_{}.json",
+                    config.results_dir, mode, config.size, config.block_size, config.run
+                ),
+                &readable
+            ).unwrap();
+        }
+    }
+
+    // optionally also to stdout as a single line, unless --json-pretty,
+    // flushed immediately so it isn't lost if we exit abruptly
+    if config.stdout_jsonl {
+        let mut stdout = io::stdout().lock();
+        writeln!(stdout, "{}", readable).unwrap();
+        stdout.flush().unwrap();
+    }
+}
+
+/// Write a file through a .tmp sibling renamed over it, so a reader never
+/// sees it half-written
+fn write_atomically(path: &str, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let tmp_path = format!("{}.tmp", path);
+    fs::write(&tmp_path, contents)?;
+    fs::rename(&tmp_path, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        iter,
+        process,
+        sync::Mutex,
+        sync::MutexGuard,
+    };
+
+    /// Held by tests that read or change the VFS_BENCH_* variables, since
+    /// every test thread shares the one environment
+    static ENV: Mutex<()> = Mutex::new(());
+
+    pub fn lock_env() -> MutexGuard<'static, ()> {
+        // a failed test poisons the lock, but leaves nothing else behind
+        ENV.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Parse the arguments following the binary's name
+    pub fn parse(args: &[&str]) -> anyhow::Result<Config> {
+        let args = iter::once("vfs-bench").chain(args.iter().copied())
+            .map(String::from)
+            .collect::<Vec<_>>();
+        Config::from_env_and_args(&args)
+    }
+
+    /// Parse a command line as the binary would, with its files going to a
+    /// scratch directory of the test's own, which is returned for cleanup
+    pub fn config(name: &str, args: &[&str]) -> (Config, String) {
+        let mut config = {
+            let _env = lock_env();
+            parse(args).unwrap()
+        };
+        let dir = env::temp_dir()
+            .join(format!("vfs-bench-{}-{}", process::id(), name));
+        fs::create_dir_all(&dir).unwrap();
+        config.scratch_dir = dir.to_str().unwrap().to_owned();
+        let dir = config.scratch_dir.clone();
+        (config, dir)
+    }
+
+    /// Parse a command line with the given VFS_BENCH_* variables set,
+    /// removing them again afterwards
+    fn parse_with_env(vars: &[(&str, &str)], args: &[&str]) -> anyhow::Result<Config> {
+        let _env = lock_env();
+        for (name, value) in vars {
+            env::set_var(name, value);
+        }
+        let config = parse(args);
+        for (name, _) in vars {
+            env::remove_var(name);
+        }
+        config
+    }
+
+    #[test]
+    fn builtin_default_without_env_or_flag() {
+        let config = parse_with_env(&[], &["write_inorder", "4096", "512"]).unwrap();
+        assert_eq!(config.seed, DEFAULT_SEED);
+        assert_eq!(config.threads, 1);
+        assert!(!config.warmup);
+    }
+
+    #[test]
+    fn env_overrides_builtin_default() {
+        let config = parse_with_env(
+            &[("VFS_BENCH_SEED", "7"), ("VFS_BENCH_THREADS", "3"), ("VFS_BENCH_WARMUP", "1")],
+            &["write_inorder", "4096", "512"],
+        ).unwrap();
+        assert_eq!(config.seed, 7);
+        assert_eq!(config.threads, 3);
+        assert!(config.warmup);
+    }
+
+    #[test]
+    fn flag_overrides_env() {
+        let config = parse_with_env(
+            &[("VFS_BENCH_SEED", "7"), ("VFS_BENCH_THREADS", "3")],
+            &["write_inorder", "4096", "512", "--seed", "9", "--threads", "2"],
+        ).unwrap();
+        assert_eq!(config.seed, 9);
+        assert_eq!(config.threads, 2);
+    }
+
+    #[test]
+    fn bad_env_is_an_error() {
+        assert!(parse_with_env(&[("VFS_BENCH_SEED", "x")], &["write_inorder", "4096", "512"]).is_err());
+        assert!(parse_with_env(&[("VFS_BENCH_WARMUP", "yes")], &["write_inorder", "4096", "512"]).is_err());
+        assert!(parse_with_env(&[("VFS_BENCH_ITERATIONS", "5")], &["write_inorder", "4096", "512"]).is_err());
+    }
+
+    #[test]
+    fn mode_limits_are_checked_when_parsing() {
+        assert!(parse_with_env(&[], &["buffered_write_inorder", "4096", "512", "--sync-mode", "data"]).is_err());
+        assert!(parse_with_env(&[], &["write_inorder", "4096", "512", "--sync-mode", "data"]).is_ok());
+        assert!(parse_with_env(&[], &["write_inorder", "4096", "512", "--phase-timings"]).is_err());
+        assert!(parse_with_env(&[], &["incremental_write_inorder", "4096", "512", "--phase-timings"]).is_ok());
+        assert!(parse_with_env(&[], &["write_random", "4096", "512", "--inline-crc"]).is_err());
+        assert!(parse_with_env(&[], &["read_inorder", "4096", "512", "--inline-crc"]).is_ok());
+        assert!(parse_with_env(&[], &["read_chunks", "4096", "256"]).is_err());
+        assert!(parse_with_env(&[], &["read_chunks", "4096", "256", "--read-chunk", "256"]).is_ok());
+        assert!(parse_with_env(&[], &["seek_then_scan", "4096", "1024"]).is_err());
+        assert!(parse_with_env(&[], &["seek_then_scan", "4096", "1024", "--scan-blocks", "4"]).is_ok());
+        assert!(parse_with_env(&[], &["write_records", "32", "16"]).is_err());
+        assert!(parse_with_env(&[], &["write_records", "32", "16", "--record-size", "32"]).is_ok());
+        assert!(parse_with_env(&[], &["clone_offsets", "4096", "4096"]).is_err());
+        assert!(parse_with_env(&[], &["clone_offsets", "8192", "4096"]).is_ok());
+        assert!(parse_with_env(&[], &["concurrent_append", "4096", "4"]).is_err());
+        assert!(parse_with_env(&[], &["concurrent_append", "4096", "8"]).is_ok());
+    }
+
+    /// Run a write mode over a file smaller than one block, which must
+    /// come out exactly size bytes, not a whole block
+    pub fn assert_partial_block(name: &str, mode: fn(&Config) -> report::Report) {
+        let (config, dir) = config(name, &[name, "100", "4096"]);
+        let report = mode(&config);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(report.extra["logical_bytes"], 100);
+    }
 }
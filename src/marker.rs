@@ -0,0 +1,26 @@
+//! Optional recognizable contents for written files
+//!
+//! ## Authors
+//!
+//! The Veracruz Development Team.
+//!
+//! ## Copyright
+//!
+//! See the file `LICENSING.markdown` in the Veracruz root directory for licensing
+//! and copyright information.
+
+use crate::Config;
+
+/// Overwrite a freshly filled buffer with the repeating --marker string, if
+/// one was given, so files left behind in a VFS dump show which benchmark
+/// produced them
+///
+/// The buffer is still filled from the PRNG first, so the PRNG advances
+/// exactly as it would otherwise and random access orders are unchanged.
+pub fn mark(config: &Config, buffer: &mut [u8]) {
+    if let Some(marker) = &config.marker {
+        for (x, m) in buffer.iter_mut().zip(marker.bytes().cycle()) {
+            *x = m;
+        }
+    }
+}
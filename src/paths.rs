@@ -0,0 +1,324 @@
+//! Benchmark of path resolution
+//!
+//! ## Authors
+//!
+//! The Veracruz Development Team.
+//!
+//! ## Copyright
+//!
+//! See the file `LICENSING.markdown` in the Veracruz root directory for licensing
+//! and copyright information.
+
+use crate::{
+    Config,
+    hint,
+    report::Report,
+    report::supported,
+    retry::with_retry,
+    scratch,
+};
+use std::{
+    convert::TryFrom,
+    fs,
+    fs::File,
+    io,
+    io::ErrorKind,
+    time::Duration,
+    time::Instant,
+};
+
+/// Number of opens timed at each depth, averaged to smooth out noise
+const OPENS_PER_DEPTH: u32 = 16;
+
+/// Number of canonicalizations timed at each path length in canonicalize
+const CANONICALIZES_PER_LENGTH: u32 = 16;
+
+/// The redundant components canonicalize adds to its paths, each
+/// repetition is three components that resolve back to where they started
+const REDUNDANT: &str = "/./a/..";
+
+
+/// Open files at increasing directory depths, up to size/block_size
+/// components deep
+///
+/// Each depth gets a file of its own, so this sweeps path length within one
+/// run and reports the mean open latency at each depth, showing how path
+/// resolution scales with the number of components.
+pub fn path_depth(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, .. } = *config;
+    let root = format!("{}/path_depth_{}_{}_{}", config.scratch_dir, size, block_size, run);
+    let max_depth = size/u64::try_from(block_size).unwrap();
+
+    // first create the nested directories, with a file at each depth,
+    // components are kept short so deep paths stay within path limits
+    let mut paths = Vec::new();
+    let mut dir = root.clone();
+    scratch::create_dir(config, &dir);
+    for _ in 0..max_depth {
+        dir.push_str("/d");
+        fs::create_dir(&dir).unwrap();
+
+        let path = format!("{}/f.txt", dir);
+        with_retry(retries, || File::create(&path)).unwrap();
+        paths.push(path);
+    }
+
+    // then measure opens at each depth
+    let mut latencies = Vec::new();
+    let mut duration = Duration::ZERO;
+
+    for path in &paths {
+        let stopwatch = Instant::now();
+
+        for _ in 0..OPENS_PER_DEPTH {
+            hint::black_box({
+                let path = hint::black_box(path);
+                with_retry(retries, || File::open(path)).unwrap()
+            });
+        }
+
+        let depth_duration = stopwatch.elapsed();
+        duration += depth_duration;
+        latencies.push((depth_duration / OPENS_PER_DEPTH).as_secs_f64());
+    }
+
+    // the files are left empty, so there's nothing to truncate here
+
+    Report::new(duration)
+        .with("max_depth", max_depth)
+        .with("opens_per_depth", OPENS_PER_DEPTH)
+        .with("open_by_depth", latencies)
+}
+
+/// Open size/block_size paths that don't exist, in an existing directory
+///
+/// This measures the error path of path resolution, which can be slow if
+/// the VFS does extensive capability lookups before failing. Every open is
+/// expected to fail with NotFound, anything else is counted as unexpected.
+pub fn open_missing(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, .. } = *config;
+    let path = format!("{}/open_missing_{}_{}_{}", config.scratch_dir, size, block_size, run);
+    scratch::create_dir(config, &path);
+
+    let count = size/u64::try_from(block_size).unwrap();
+    let mut unexpected = 0u64;
+    let stopwatch = Instant::now();
+
+    // each open looks up a different name, so a VFS caching negative
+    // lookups can't answer from a single cached miss
+    for i in 0..count {
+        let path = format!("{}/{:09x}.txt", path, i);
+
+        let res = hint::black_box({
+            let path = hint::black_box(&path);
+            with_retry(retries, || File::open(path))
+        });
+        match res {
+            Err(err) if err.kind() == ErrorKind::NotFound => {}
+            _ => unexpected += 1,
+        }
+    }
+
+    let duration = stopwatch.elapsed();
+
+    // nothing was created but the directory
+    fs::remove_dir(&path).unwrap();
+
+    Report::new(duration)
+        .with("opens", count)
+        .with("opens_per_sec", count as f64 / duration.as_secs_f64())
+        .with("unexpected_results", unexpected)
+}
+
+/// Canonicalize paths to one file padded with 0 up to size/block_size
+/// repetitions of `./a/..`
+///
+/// Resolving `.`, `..` and symlinks is work the other benchmarks skip by
+/// using clean paths. The mean latency at each number of repetitions is
+/// reported, along with a least-squares fit of the cost per redundant
+/// component, so it's clear whether the cost grows with the path. Results
+/// not naming the file, and NotFound errors from a VFS that can't follow
+/// `..`, are counted rather than treated as fatal.
+pub fn canonicalize(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, .. } = *config;
+    let root = format!("{}/canonicalize_{}_{}_{}", config.scratch_dir, size, block_size, run);
+    let max_repeats = size/u64::try_from(block_size).unwrap();
+    scratch::create_dir(config, &root);
+    fs::create_dir(format!("{}/a", root)).unwrap();
+    let target = format!("{}/a/f.txt", root);
+    with_retry(retries, || File::create(&target)).unwrap();
+
+    // the clean path resolves to what every padded path should
+    let expected = supported!(with_retry(retries, || fs::canonicalize(&target)));
+
+    let mut latencies = Vec::new();
+    let mut duration = Duration::ZERO;
+    let mut not_found = 0u64;
+    let mut mismatched = 0u64;
+
+    for repeats in 0..=max_repeats {
+        let path = format!("{}{}/a/f.txt",
+            root, REDUNDANT.repeat(usize::try_from(repeats).unwrap())
+        );
+
+        let stopwatch = Instant::now();
+
+        for _ in 0..CANONICALIZES_PER_LENGTH {
+            let res = hint::black_box({
+                let path = hint::black_box(&path);
+                with_retry(retries, || fs::canonicalize(path))
+            });
+            match res {
+                Ok(resolved) if resolved == expected => {}
+                Ok(_) => mismatched += 1,
+                Err(err) if err.kind() == ErrorKind::Unsupported => {
+                    return Report::unsupported(&err);
+                }
+                Err(err) if err.kind() == ErrorKind::NotFound => not_found += 1,
+                Err(err) => panic!("Can't canonicalize {}: {}", path, err),
+            }
+        }
+
+        let length_duration = stopwatch.elapsed();
+        duration += length_duration;
+        latencies.push((length_duration / CANONICALIZES_PER_LENGTH).as_secs_f64());
+    }
+
+    // least-squares slope of latency against the number of components
+    let components = REDUNDANT.matches('/').count() as f64;
+    let n = latencies.len() as f64;
+    let mean_x = (n - 1.0) / 2.0;
+    let mean_y = latencies.iter().sum::<f64>() / n;
+    let (cov, var) = latencies.iter().enumerate()
+        .fold((0.0, 0.0), |(cov, var), (x, y)| {
+            let dx = x as f64 - mean_x;
+            (cov + dx*(y - mean_y), var + dx*dx)
+        });
+    let per_component = if var > 0.0 { cov / var / components } else { 0.0 };
+
+    // the file is left empty, so there's nothing to truncate here
+
+    Report::new(duration)
+        .with("max_redundant_components", max_repeats*components as u64)
+        .with("canonicalizes_per_length", CANONICALIZES_PER_LENGTH)
+        .with("canonicalize_by_repeats", latencies)
+        .with("seconds_per_component", per_component)
+        .with("not_found", not_found)
+        .with("mismatched", mismatched)
+}
+
+/// Open a file, then --pollute other files, then the first file again,
+/// comparing the two opens of the first file
+///
+/// If the VFS caches path lookups or metadata, the opens in between may
+/// evict the first file's entry, making the second open as slow as the
+/// first, or slower, rather than faster.
+pub fn metadata_eviction(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, pollute, .. } = *config;
+    let path = format!("{}/metadata_eviction_{}_{}_{}", config.scratch_dir, size, block_size, run);
+    let target = format!("{}/target.txt", path);
+    scratch::create_dir(config, &path);
+
+    // first create the files, empty since only opening is measured
+    with_retry(retries, || File::create(&target)).unwrap();
+    let others = (0..pollute)
+        .map(|i| format!("{}/{:09x}.txt", path, i))
+        .collect::<Vec<_>>();
+    for other in &others {
+        with_retry(retries, || File::create(other)).unwrap();
+    }
+
+    let open = |path: &String| {
+        let stopwatch = Instant::now();
+        hint::black_box({
+            let path = hint::black_box(path);
+            with_retry(retries, || File::open(path)).unwrap()
+        });
+        stopwatch.elapsed()
+    };
+
+    // then open the target, pollute any caches, and open the target again
+    let first_duration = open(&target);
+    let pollute_duration = others.iter().map(open).sum::<Duration>();
+    let reopen_duration = open(&target);
+
+    // the files are left empty, so there's nothing to truncate here
+
+    Report::new(first_duration + reopen_duration)
+        .with("pollute", pollute)
+        .with("first_open", first_duration.as_secs_f64())
+        .with("reopen", reopen_duration.as_secs_f64())
+        .with("reopen_ratio",
+            reopen_duration.as_secs_f64() / first_duration.as_secs_f64()
+        )
+        .with("pollute_runtime", pollute_duration.as_secs_f64())
+}
+
+/// Create size/block_size symlinks to one file, then read each link's
+/// target back with read_link
+///
+/// Creating and reading the links are timed separately so their costs can
+/// be compared, read_link doesn't follow the link, so this only measures
+/// reading the link itself.
+pub fn read_link(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, .. } = *config;
+    let path = format!("{}/read_link_{}_{}_{}", config.scratch_dir, size, block_size, run);
+    let target = format!("{}/target.txt", path);
+    scratch::create_dir(config, &path);
+    with_retry(retries, || File::create(&target)).unwrap();
+
+    let count = size/u64::try_from(block_size).unwrap();
+    let links = (0..count)
+        .map(|i| format!("{}/{:09x}.lnk", path, i))
+        .collect::<Vec<_>>();
+
+    // first measure creating the links
+    let stopwatch = Instant::now();
+
+    for link in &links {
+        hint::black_box({
+            let link = hint::black_box(link);
+            supported!(with_retry(retries, || symlink(&target, link)));
+        });
+    }
+
+    let create_duration = stopwatch.elapsed();
+
+    // then reading them
+    let stopwatch = Instant::now();
+
+    for link in &links {
+        hint::black_box(
+            supported!(with_retry(retries, || fs::read_link(hint::black_box(link))))
+        );
+    }
+
+    let duration = stopwatch.elapsed();
+
+    // links have no contents, but remove them so a VFS copying the tree
+    // back doesn't have to deal with them
+    for link in &links {
+        fs::remove_file(link).unwrap();
+    }
+
+    Report::new(duration)
+        .with("links", count)
+        .with("read_links_per_sec", count as f64 / duration.as_secs_f64())
+        .with("symlink_runtime", create_duration.as_secs_f64())
+        .with("symlinks_per_sec", count as f64 / create_duration.as_secs_f64())
+}
+
+/// Create a symlink, std only has this for Unix, and wasm32-wasi's is still
+/// unstable
+fn symlink(target: &str, link: &str) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(target, link)
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = (target, link);
+        Err(io::Error::new(ErrorKind::Unsupported, "symlinks are only supported on Unix"))
+    }
+}
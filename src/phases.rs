@@ -0,0 +1,86 @@
+//! Optional breakdown of time spent in each kind of file operation
+//!
+//! ## Authors
+//!
+//! The Veracruz Development Team.
+//!
+//! ## Copyright
+//!
+//! See the file `LICENSING.markdown` in the Veracruz root directory for licensing
+//! and copyright information.
+
+use crate::Config;
+use serde_json::{
+    Map,
+    Value,
+};
+use std::time::{
+    Duration,
+    Instant,
+};
+
+/// Kind of operation a block's time is spent in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    Open,
+    Seek,
+    Io,
+    Flush,
+}
+
+impl Phase {
+    const ALL: [Phase; 4] = [Phase::Open, Phase::Seek, Phase::Io, Phase::Flush];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Phase::Open => "open",
+            Phase::Seek => "seek",
+            Phase::Io => "io",
+            Phase::Flush => "flush",
+        }
+    }
+}
+
+/// Time spent in each phase, summed over every block, recorded only when
+/// --phase-timings is given since the extra clock reads add their own
+/// overhead
+///
+/// A profiler may not run inside the enclave, this gives a coarse picture
+/// of where the time goes without one.
+pub struct Phases {
+    totals: Option<[Duration; 4]>,
+}
+
+impl Phases {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            totals: config.phase_timings.then_some([Duration::ZERO; 4]),
+        }
+    }
+
+    /// Run an operation in the given phase, timing it if enabled
+    pub fn time<T>(&mut self, phase: Phase, f: impl FnOnce() -> T) -> T {
+        match &mut self.totals {
+            Some(totals) => {
+                let stopwatch = Instant::now();
+                let res = f();
+                totals[phase as usize] += stopwatch.elapsed();
+                res
+            }
+            None => f(),
+        }
+    }
+
+    /// Seconds spent in each phase, by name, if enabled
+    pub fn breakdown(&self) -> Option<Value> {
+        let totals = self.totals.as_ref()?;
+        Some(Value::Object(
+            Phase::ALL.iter()
+                .map(|&phase| (
+                    phase.name().to_owned(),
+                    Value::from(totals[phase as usize].as_secs_f64()),
+                ))
+                .collect::<Map<_, _>>()
+        ))
+    }
+}
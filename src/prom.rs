@@ -0,0 +1,73 @@
+//! Prometheus textfile output, for node_exporter's textfile collector
+//!
+//! ## Authors
+//!
+//! The Veracruz Development Team.
+//!
+//! ## Copyright
+//!
+//! See the file `LICENSING.markdown` in the Veracruz root directory for licensing
+//! and copyright information.
+
+use crate::Output;
+use anyhow::bail;
+use std::{
+    cmp::min,
+    convert::TryFrom,
+    fmt::Write,
+    str::FromStr,
+};
+
+/// Where results go when not appended to --ndjson
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// one result_*.json in the results directory per result
+    Json,
+    /// one metrics_*.prom in the results directory per result, the textfile
+    /// collector reads every *.prom there
+    Prom,
+}
+
+impl FromStr for Format {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "json" => Ok(Format::Json),
+            "prom" => Ok(Format::Prom),
+            _ => bail!("Unknown format {:?}, expected json or prom", s),
+        }
+    }
+}
+
+/// Render a result as Prometheus metrics, labelled with its mode, size,
+/// block_size and run
+///
+/// The op count is the number a full run does, or the number actually
+/// completed if the run was cut short, and throughput is derived from it.
+pub fn render(output: &Output) -> String {
+    let labels = format!("mode=\"{}\",size=\"{}\",block_size=\"{}\",run=\"{}\"",
+        output.name, output.size, output.block_size, output.run
+    );
+
+    let ops = output.ops();
+    let bytes = min(ops*u64::try_from(output.block_size).unwrap(), output.size);
+
+    // metrics are always in seconds, whatever --time-unit says
+    let runtime = output.runtime / output.time_unit.per_second();
+
+    let mut prom = String::new();
+    for (name, kind, help, value) in [
+        ("vfs_bench_runtime_seconds", "gauge",
+            "Measured runtime of the benchmark", runtime),
+        ("vfs_bench_throughput_bytes_per_second", "gauge",
+            "Bytes operated on per second of measured runtime", bytes as f64 / runtime),
+        ("vfs_bench_ops", "gauge",
+            "Number of block operations measured", ops as f64),
+    ] {
+        writeln!(prom, "# HELP {} {}", name, help).unwrap();
+        writeln!(prom, "# TYPE {} {}", name, kind).unwrap();
+        writeln!(prom, "{}{{{}}} {}", name, labels, value).unwrap();
+    }
+    prom
+}
@@ -0,0 +1,251 @@
+//! Benchmark of renaming files
+//!
+//! ## Authors
+//!
+//! The Veracruz Development Team.
+//!
+//! ## Copyright
+//!
+//! See the file `LICENSING.markdown` in the Veracruz root directory for licensing
+//! and copyright information.
+
+use crate::{
+    Config,
+    hint,
+    marker::mark,
+    report::Report,
+    report::supported,
+    retry::with_retry,
+    scratch,
+    rng::Prng,
+    small_files::create_files,
+};
+use std::{
+    cmp::min,
+    convert::TryFrom,
+    fs,
+    fs::File,
+    fs::OpenOptions,
+    io::Write,
+    io::Read,
+    mem,
+    time::Duration,
+    time::Instant,
+};
+
+
+/// Repeatedly replace a file by writing a temporary file and renaming it
+/// over the target
+///
+/// This is the usual safe-write idiom, so only the rename is timed. With
+/// --verify the target is read back through a fresh handle after each
+/// rename, and any contents that are neither fully the old nor fully the new
+/// version are counted as torn.
+pub fn rename_replace(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, verify, .. } = *config;
+    let path = format!("{}/rename_replace_{}_{}_{}.txt", config.scratch_dir, size, block_size, run);
+    let tmp_path = format!("{}.tmp", path);
+
+    // each version is filled with its own byte, so a mix of versions is
+    // easy to spot
+    let mut buffer = vec![0u8; block_size];
+    let mut file = with_retry(retries, || File::create(&path)).unwrap();
+    with_retry(retries, || file.write_all(&buffer)).unwrap();
+    file.flush().unwrap();
+    mem::drop(file);
+
+    let mut readback = Vec::with_capacity(block_size);
+    let mut torn = 0u64;
+    let mut duration = Duration::ZERO;
+
+    let count = size/u64::try_from(block_size).unwrap();
+    for i in 1..=count {
+        let old = buffer[0];
+        let new = i as u8;
+        buffer.fill(new);
+
+        // writing the new version isn't measured
+        let mut file = with_retry(retries, || File::create(&tmp_path)).unwrap();
+        with_retry(retries, || file.write_all(&buffer)).unwrap();
+        file.flush().unwrap();
+        mem::drop(file);
+
+        let stopwatch = Instant::now();
+
+        hint::black_box({
+            let tmp_path = hint::black_box(&tmp_path);
+            supported!(with_retry(retries, || fs::rename(tmp_path, &path)), {
+                File::create(&path).unwrap();
+                fs::remove_file(tmp_path).unwrap();
+            });
+        });
+
+        duration += stopwatch.elapsed();
+
+        if verify {
+            readback.clear();
+            let mut file = with_retry(retries, || File::open(&path)).unwrap();
+            with_retry(retries, || file.read_to_end(&mut readback)).unwrap();
+            let whole = readback.len() == block_size && (
+                readback.iter().all(|&x| x == new)
+                    || readback.iter().all(|&x| x == old)
+            );
+            if !whole {
+                torn += 1;
+            }
+        }
+    }
+
+    let file = with_retry(retries, || File::create(&path)).unwrap();
+
+    // Truncate the file! Otherwise Veracruz may try to copy it back over
+    // into the user's fs, which is a waste of (significant) time...
+    //
+    file.set_len(0).unwrap();
+
+    let report = Report::new(duration)
+        .with("renames", count)
+        .with("renames_per_sec", count as f64 / duration.as_secs_f64())
+        .with("verify", verify);
+
+    if verify {
+        report.with("torn_reads", torn)
+    } else {
+        report
+    }
+}
+
+/// Rename a directory populated with size/block_size files
+///
+/// On most filesystems this is a single metadata update however many files
+/// the directory holds, but a VFS may need to move every entry. Afterwards
+/// every file is checked to still be there, at full length, under the new
+/// path.
+pub fn rename_dir(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, .. } = *config;
+    let path = format!("{}/rename_dir_{}_{}_{}", config.scratch_dir, size, block_size, run);
+    let from = format!("{}/a", path);
+    let to = format!("{}/b", path);
+    scratch::create_dir(config, &path);
+    fs::create_dir(&from).unwrap();
+
+    // first populate the directory
+    create_files(config, &from);
+
+    // then measure moving it
+    let stopwatch = Instant::now();
+
+    hint::black_box({
+        let from = hint::black_box(&from);
+        supported!(
+            with_retry(retries, || fs::rename(from, &to)),
+            fs::remove_dir_all(&path).unwrap()
+        );
+    });
+
+    let duration = stopwatch.elapsed();
+
+    let count = size/u64::try_from(block_size).unwrap();
+    let intact = (0..count).all(|i| {
+        let path = format!("{}/{:09x}.txt", to, i);
+        fs::metadata(&path)
+            .map(|metadata| metadata.len() == u64::try_from(block_size).unwrap())
+            .unwrap_or(false)
+    }) && fs::metadata(&from).is_err();
+
+    // Clean up! Otherwise Veracruz may try to copy it back over
+    // into the user's fs, which is a waste of (significant) time...
+    //
+    for i in 0..count {
+        let path = format!("{}/{:09x}.txt", to, i);
+        if let Ok(file) = with_retry(retries, || File::create(&path)) {
+            file.set_len(0).unwrap();
+        }
+    }
+
+    Report::new(duration)
+        .with("files", count)
+        .with("intact", intact)
+}
+
+/// Append a large file's worth of blocks to a log, rotating it every
+/// --rotate-bytes by renaming it to a numbered archive and starting a new
+/// one
+///
+/// The rotations are timed along with the appends, so this is the
+/// throughput of the whole pattern as a logger would see it.
+pub fn log_rotate(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, rotate_bytes, .. } = *config;
+    let path = format!("{}/log_rotate_{}_{}_{}", config.scratch_dir, size, block_size, run);
+    let log_path = format!("{}/log.txt", path);
+    let mut prng = Prng::new(config.prng, seed);
+    let mut buffer = vec![0u8; block_size];
+    scratch::create_dir(config, &path);
+
+    let open = || with_retry(retries, || {
+        OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(&log_path)
+    });
+
+    let mut file = open().unwrap();
+    let mut logged = 0u64;
+    let mut rotations = 0u64;
+    let stopwatch = Instant::now();
+
+    for i in (0..size).step_by(block_size) {
+        let step_size = usize::try_from(
+            min(i+u64::try_from(block_size).unwrap(), size) - i
+        ).unwrap();
+        for (j, x) in
+            (&mut prng)
+                .take(step_size)
+                .enumerate()
+        {
+            buffer[j] = x as u8;
+        }
+        mark(config, &mut buffer);
+
+        hint::black_box({
+            if logged >= rotate_bytes {
+                file.flush().unwrap();
+                let archive = format!("{}/log.{}.txt", path, rotations);
+                supported!(
+                    with_retry(retries, || fs::rename(&log_path, &archive)),
+                    fs::remove_dir_all(&path).unwrap()
+                );
+                file = open().unwrap();
+                logged = 0;
+                rotations += 1;
+            }
+
+            let input = hint::black_box(&buffer[..step_size]);
+            with_retry(retries, || file.write_all(input)).unwrap();
+            logged += u64::try_from(step_size).unwrap();
+        });
+    }
+
+    hint::black_box({
+        file.flush().unwrap();
+    });
+
+    let duration = stopwatch.elapsed();
+    mem::drop(file);
+
+    // Truncate the files! Otherwise Veracruz may try to copy them back over
+    // into the user's fs, which is a waste of (significant) time...
+    //
+    for archive in (0..rotations)
+        .map(|n| format!("{}/log.{}.txt", path, n))
+        .chain([log_path.clone()])
+    {
+        let file = with_retry(retries, || File::create(&archive)).unwrap();
+        file.set_len(0).unwrap();
+    }
+
+    Report::new(duration)
+        .with("rotate_bytes", rotate_bytes)
+        .with("rotations", rotations)
+        .with("bytes_per_sec", size as f64 / duration.as_secs_f64())
+}
@@ -0,0 +1,98 @@
+//! Repeating a benchmark, for a fixed time budget or over several seeds
+//!
+//! ## Authors
+//!
+//! The Veracruz Development Team.
+//!
+//! ## Copyright
+//!
+//! See the file `LICENSING.markdown` in the Veracruz root directory for licensing
+//! and copyright information.
+
+use crate::{
+    Config,
+    report::Report,
+    interrupt::interrupted,
+};
+use std::{
+    convert::TryFrom,
+    time::Duration,
+};
+
+/// Run a benchmark over and over until its measured runtimes add up to
+/// budget, for steady-state numbers
+///
+/// Only measured runtime counts against the budget, so setup and cleanup
+/// don't eat into it. Each iteration runs as the next run number, so it
+/// gets fresh paths and never trips over what the previous one left
+/// behind. The returned report is the last iteration's, with its runtime
+/// replaced by the mean over all iterations.
+pub fn repeat_until(
+    config: &Config,
+    benchmark: fn(&Config) -> Report,
+    budget: Duration
+) -> Report {
+    let mut config = config.clone();
+    let mut runtimes = Vec::new();
+    let mut total = Duration::ZERO;
+
+    loop {
+        let report = benchmark(&config);
+        total += report.runtime;
+        runtimes.push(report.runtime);
+
+        // an unsupported mode won't become supported by trying again
+        if total >= budget || !report.supported || interrupted() {
+            let iterations = u32::try_from(runtimes.len()).unwrap();
+            let mut report = report
+                .with("iterations", iterations)
+                .with("repeat_until", budget.as_secs_f64())
+                .with_latencies("iteration", &mut runtimes);
+            report.runtime = total / iterations;
+            return report;
+        }
+
+        config.run += 1;
+    }
+}
+
+/// Run a benchmark once for each of seeds seed, seed+1, ... seed+n-1,
+/// averaging out any artifacts of one particular PRNG layout
+///
+/// Every run is still deterministic, and as with repeat_until each runs as
+/// the next run number. The returned report is the last run's, with its
+/// runtime replaced by the mean over all seeds, and the standard deviation
+/// and each seed's runtime attached.
+pub fn seed_sweep(
+    config: &Config,
+    benchmark: fn(&Config) -> Report,
+    n: u32
+) -> Report {
+    let mut config = config.clone();
+    let mut runtimes = Vec::new();
+
+    loop {
+        let report = benchmark(&config);
+        runtimes.push(report.runtime.as_secs_f64());
+
+        if runtimes.len() == usize::try_from(n).unwrap()
+            || !report.supported
+            || interrupted()
+        {
+            let mean = runtimes.iter().sum::<f64>() / runtimes.len() as f64;
+            let variance = runtimes.iter()
+                .map(|runtime| (runtime - mean).powi(2))
+                .sum::<f64>() / runtimes.len() as f64;
+
+            let mut report = report
+                .with("seeds", runtimes.len())
+                .with("runtime_stddev", variance.sqrt())
+                .with("seed_runtimes", runtimes);
+            report.runtime = Duration::from_secs_f64(mean);
+            return report;
+        }
+
+        config.seed = config.seed.wrapping_add(1);
+        config.run += 1;
+    }
+}
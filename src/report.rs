@@ -0,0 +1,201 @@
+//! Results reported by each benchmark
+//!
+//! ## Authors
+//!
+//! The Veracruz Development Team.
+//!
+//! ## Copyright
+//!
+//! See the file `LICENSING.markdown` in the Veracruz root directory for licensing
+//! and copyright information.
+
+use crate::{
+    Config,
+    coverage::Coverage,
+    interrupt::interrupted,
+    phases::Phases,
+};
+use serde_json::{
+    Map,
+    Value,
+};
+use std::{
+    fmt,
+    fs::Metadata,
+    io,
+    time::Duration,
+};
+#[cfg(unix)]
+use std::os::unix::fs::MetadataExt;
+#[cfg(all(unix, feature = "peak_rss"))]
+use std::{
+    convert::TryFrom,
+    mem,
+};
+
+/// Unwrap the result of an I/O operation, unless the VFS doesn't support
+/// the operation, in which case the enclosing benchmark returns a Report
+/// saying so
+///
+/// This lets a sweep across all modes complete on backends missing some
+/// features. The early return skips the benchmark's own cleanup, so a
+/// benchmark that has already written files passes a cleanup expression as
+/// the second argument, which is run before returning. Otherwise the files
+/// are left behind and the next run of the mode finds them stale.
+macro_rules! supported {
+    ($res:expr) => {
+        crate::report::supported!($res, ())
+    };
+    ($res:expr, $cleanup:expr) => {
+        match $res {
+            Err(err) if err.kind() == std::io::ErrorKind::Unsupported => {
+                $cleanup;
+                return crate::report::Report::unsupported(&err);
+            }
+            res => res.unwrap(),
+        }
+    };
+}
+pub(crate) use supported;
+
+/// Result of a single benchmark run
+///
+/// The runtime is the headline number written to the results, benchmarks
+/// can attach any additional measurements which are emitted alongside it.
+pub struct Report {
+    pub runtime: Duration,
+    /// false if the VFS doesn't support an operation the benchmark needs,
+    /// the runtime is then zero
+    pub supported: bool,
+    pub extra: Map<String, Value>,
+}
+
+impl Report {
+    pub fn new(runtime: Duration) -> Self {
+        Self {
+            runtime,
+            supported: true,
+            extra: Map::new(),
+        }
+    }
+
+    /// Result of a benchmark that couldn't run because the VFS doesn't
+    /// support one of its operations
+    pub fn unsupported(err: &io::Error) -> Self {
+        Self {
+            runtime: Duration::ZERO,
+            supported: false,
+            extra: Map::new(),
+        }.with("unsupported_error", err.to_string())
+    }
+
+    /// Result of a benchmark that couldn't run for some other reason, such
+    /// as failing to allocate its buffers, the runtime is then zero
+    pub fn failed(err: &impl fmt::Display) -> Self {
+        Self::new(Duration::ZERO)
+            .with("error", err.to_string())
+    }
+
+    /// Attach an additional named measurement
+    pub fn with(mut self, name: &str, value: impl Into<Value>) -> Self {
+        self.extra.insert(name.to_owned(), value.into());
+        self
+    }
+
+    /// Attach how many blocks the benchmark completed, if it was cut short
+    /// by an interruption or may have been by --max-ops
+    pub fn with_progress(self, config: &Config, blocks: u64) -> Self {
+        if interrupted() || config.max_ops.is_some() {
+            self.with("blocks_completed", blocks)
+        } else {
+            self
+        }
+    }
+
+    /// Attach the footprint of a written file, on Unix this includes the
+    /// bytes actually allocated so we can see the effects of compression or
+    /// sparseness in the backing store
+    pub fn with_footprint(self, metadata: &Metadata) -> Self {
+        let report = self.with("logical_bytes", metadata.len());
+
+        // st_blocks is always in 512-byte units, regardless of st_blksize
+        #[cfg(unix)]
+        let report = report.with("allocated_bytes", metadata.blocks()*512);
+
+        report
+    }
+
+    /// Attach the peak resident set size of the process so far, to check
+    /// that the benchmark's own buffers aren't what's limiting it
+    ///
+    /// This uses getrusage on Unix with the peak_rss feature, and otherwise
+    /// falls back to VmHWM from /proc/self/status on Linux. Elsewhere the
+    /// field is simply omitted.
+    pub fn with_peak_rss(self) -> Self {
+        #[cfg(all(unix, feature = "peak_rss"))]
+        {
+            let mut usage = mem::MaybeUninit::<libc::rusage>::uninit();
+            // SAFETY: getrusage only writes to the rusage we pass it, which
+            // we only read if it reports success
+            let res = unsafe { libc::getrusage(libc::RUSAGE_SELF, usage.as_mut_ptr()) };
+            if res == 0 {
+                let usage = unsafe { usage.assume_init() };
+                // ru_maxrss is in bytes on macOS, but kilobytes elsewhere
+                let scale = if cfg!(target_os = "macos") { 1 } else { 1024 };
+                return self.with("peak_rss_bytes", u64::try_from(usage.ru_maxrss).unwrap()*scale);
+            }
+        }
+
+        #[cfg(all(target_os = "linux", not(feature = "peak_rss")))]
+        {
+            // the line looks like "VmHWM:     1234 kB"
+            let hwm = std::fs::read_to_string("/proc/self/status").ok()
+                .and_then(|status| {
+                    status.lines()
+                        .find_map(|line| line.strip_prefix("VmHWM:"))
+                        .and_then(|kb| kb.trim().strip_suffix("kB"))
+                        .and_then(|kb| kb.trim().parse::<u64>().ok())
+                });
+            if let Some(kb) = hwm {
+                return self.with("peak_rss_bytes", kb*1024);
+            }
+        }
+
+        self
+    }
+
+    /// Attach how many distinct blocks a random mode touched, and what
+    /// fraction of the file's blocks that is
+    pub fn with_coverage(self, coverage: &Coverage) -> Self {
+        self.with("unique_blocks_touched", coverage.unique())
+            .with("coverage_fraction", coverage.fraction())
+    }
+
+    /// Attach the seconds spent opening, seeking, doing I/O and flushing,
+    /// as phase_seconds, if --phase-timings was given
+    pub fn with_phases(self, phases: &Phases) -> Self {
+        match phases.breakdown() {
+            Some(breakdown) => self.with("phase_seconds", breakdown),
+            None => self,
+        }
+    }
+
+    /// Attach a summary of a set of latencies, as name_min, name_median,
+    /// name_p99 and name_max in seconds
+    pub fn with_latencies(self, name: &str, latencies: &mut [Duration]) -> Self {
+        if latencies.is_empty() {
+            return self;
+        }
+
+        latencies.sort_unstable();
+        let at = |q: f64| {
+            let i = ((latencies.len()-1) as f64 * q).round() as usize;
+            latencies[i].as_secs_f64()
+        };
+
+        self.with(&format!("{}_min", name), at(0.0))
+            .with(&format!("{}_median", name), at(0.5))
+            .with(&format!("{}_p99", name), at(0.99))
+            .with(&format!("{}_max", name), at(1.0))
+    }
+}
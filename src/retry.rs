@@ -0,0 +1,53 @@
+//! Retrying of transient VFS errors
+//!
+//! ## Authors
+//!
+//! The Veracruz Development Team.
+//!
+//! ## Copyright
+//!
+//! See the file `LICENSING.markdown` in the Veracruz root directory for licensing
+//! and copyright information.
+
+use std::{
+    io,
+    io::ErrorKind,
+    sync::atomic::AtomicU64,
+    sync::atomic::Ordering,
+    thread,
+    time::Duration,
+};
+
+/// Backoff before the first retry, doubled on each following retry
+const BACKOFF: Duration = Duration::from_micros(100);
+
+/// Number of retries performed so far, reported with the results
+static RETRIES: AtomicU64 = AtomicU64::new(0);
+
+/// Run an I/O operation, retrying up to `attempts` times with a short
+/// backoff if it is interrupted
+///
+/// Any other error is returned immediately, so real failures are not masked.
+pub fn with_retry<T>(
+    attempts: u32,
+    mut f: impl FnMut() -> io::Result<T>
+) -> io::Result<T> {
+    let mut backoff = BACKOFF;
+    let mut attempt = 0;
+    loop {
+        match f() {
+            Err(err) if err.kind() == ErrorKind::Interrupted && attempt < attempts => {
+                RETRIES.fetch_add(1, Ordering::Relaxed);
+                thread::sleep(backoff);
+                backoff *= 2;
+                attempt += 1;
+            }
+            res => return res,
+        }
+    }
+}
+
+/// Number of retries performed so far
+pub fn retries() -> u64 {
+    RETRIES.load(Ordering::Relaxed)
+}
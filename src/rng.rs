@@ -0,0 +1,110 @@
+//! Deterministic pseudo-random number generators
+//!
+//! ## Authors
+//!
+//! The Veracruz Development Team.
+//!
+//! ## Copyright
+//!
+//! See the file `LICENSING.markdown` in the Veracruz root directory for licensing
+//! and copyright information.
+
+use anyhow::bail;
+use std::str::FromStr;
+
+/// Which generator to use for file contents and access orders
+///
+/// xorshift64 has known statistical weaknesses, the others let us rule out
+/// its patterns interacting with a content-addressed backend.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Algorithm {
+    Xorshift64,
+    Pcg,
+    Splitmix64,
+}
+
+impl Algorithm {
+    pub fn name(self) -> &'static str {
+        match self {
+            Algorithm::Xorshift64 => "xorshift64",
+            Algorithm::Pcg => "pcg",
+            Algorithm::Splitmix64 => "splitmix64",
+        }
+    }
+}
+
+impl FromStr for Algorithm {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "xorshift64" => Ok(Algorithm::Xorshift64),
+            "pcg" => Ok(Algorithm::Pcg),
+            "splitmix64" => Ok(Algorithm::Splitmix64),
+            _ => bail!("Unknown PRNG {:?}, expected xorshift64, pcg or splitmix64", s),
+        }
+    }
+}
+
+/// Derive a seed for one run from the base seed, so runs of a sweep each
+/// get different data and access orders
+///
+/// This is the splitmix64 output function, so neighbouring runs get
+/// unrelated seeds. The result is kept odd, and so non-zero, which
+/// xorshift requires.
+pub fn seed_for_run(seed: u64, run: u32) -> u64 {
+    let mut z = seed.wrapping_add(u64::from(run).wrapping_mul(0x9e3779b97f4a7c15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    (z ^ (z >> 31)) | 1
+}
+
+/// A seeded generator, iterating over its outputs
+pub enum Prng {
+    Xorshift64(u64),
+    Pcg(u64),
+    Splitmix64(u64),
+}
+
+impl Prng {
+    /// Create a generator, xorshift64 only produces zeros from a zero seed
+    pub fn new(algorithm: Algorithm, seed: u64) -> Self {
+        match algorithm {
+            Algorithm::Xorshift64 => Prng::Xorshift64(seed),
+            Algorithm::Pcg => Prng::Pcg(seed),
+            Algorithm::Splitmix64 => Prng::Splitmix64(seed),
+        }
+    }
+}
+
+impl Iterator for Prng {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        Some(match self {
+            Prng::Xorshift64(x) => {
+                *x ^= *x << 13;
+                *x ^= *x >> 7;
+                *x ^= *x << 17;
+                *x
+            }
+            // PCG-RXS-M-XS, with 64 bits of state and output
+            Prng::Pcg(state) => {
+                let x = *state;
+                *state = x
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                let word = ((x >> ((x >> 59) + 5)) ^ x)
+                    .wrapping_mul(12605985483623766277);
+                (word >> 43) ^ word
+            }
+            Prng::Splitmix64(state) => {
+                *state = state.wrapping_add(0x9e3779b97f4a7c15);
+                let mut z = *state;
+                z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+                z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+                z ^ (z >> 31)
+            }
+        })
+    }
+}
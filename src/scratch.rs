@@ -0,0 +1,82 @@
+//! Creating the per-run directories under the scratch directory
+//!
+//! ## Authors
+//!
+//! The Veracruz Development Team.
+//!
+//! ## Copyright
+//!
+//! See the file `LICENSING.markdown` in the Veracruz root directory for licensing
+//! and copyright information.
+
+use crate::Config;
+use std::{
+    fs,
+    io,
+    path::Path,
+};
+
+/// Create the top-level directory of a run, starting it empty
+///
+/// Finished runs truncate their files rather than removing them, so a
+/// directory left over holding only empty files, or none, is just cleared.
+/// One still holding data is from an aborted run, and reusing it would mix
+/// in its files, so that is an error naming the stale path. With --fresh
+/// it is removed, with everything in it, regardless.
+pub fn create_dir(config: &Config, path: &str) {
+    match holds_data(Path::new(path)) {
+        Ok(true) if !config.fresh => {
+            panic!("{} is left over from an earlier run, remove it or pass --fresh", path);
+        }
+        Ok(_) => fs::remove_dir_all(path).unwrap(),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+        Err(err) => panic!("Can't check {} for stale files: {}", path, err),
+    }
+
+    fs::create_dir_all(path).unwrap();
+}
+
+/// Whether any file under path, at any depth, is non-empty, symlinks are
+/// not followed
+fn holds_data(path: &Path) -> io::Result<bool> {
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            if holds_data(&entry.path())? {
+                return Ok(true);
+            }
+        } else if file_type.is_file() && entry.metadata()?.len() > 0 {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::config;
+    use std::panic;
+
+    #[test]
+    fn empty_leftovers_are_cleared_but_data_is_stale() {
+        let (mut config, dir) = config("scratch", &["small_write_inorder", "4096", "512"]);
+        let path = format!("{}/run", dir);
+
+        // what a finished run leaves behind
+        fs::create_dir_all(format!("{}/sub", path)).unwrap();
+        fs::write(format!("{}/sub/a.txt", path), b"").unwrap();
+        create_dir(&config, &path);
+        assert!(fs::read_dir(&path).unwrap().next().is_none());
+
+        // and an aborted one
+        fs::write(format!("{}/b.txt", path), b"data").unwrap();
+        assert!(panic::catch_unwind(|| create_dir(&config, &path)).is_err());
+        config.fresh = true;
+        create_dir(&config, &path);
+        assert!(fs::read_dir(&path).unwrap().next().is_none());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,123 @@
+//! Optional recording of the order blocks are visited in
+//!
+//! ## Authors
+//!
+//! The Veracruz Development Team.
+//!
+//! ## Copyright
+//!
+//! See the file `LICENSING.markdown` in the Veracruz root directory for licensing
+//! and copyright information.
+
+use crate::Config;
+use anyhow::{bail, Context};
+use std::{
+    convert::TryFrom,
+    fs,
+    fs::File,
+    io::BufWriter,
+    io::Write,
+    str::FromStr,
+};
+
+/// What was done to a block
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
+    Read,
+    Write,
+}
+
+impl Op {
+    pub fn name(self) -> &'static str {
+        match self {
+            Op::Read => "read",
+            Op::Write => "write",
+        }
+    }
+}
+
+impl FromStr for Op {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "read" => Ok(Op::Read),
+            "write" => Ok(Op::Write),
+            _ => bail!("Unknown operation {:?}, expected read or write", s),
+        }
+    }
+}
+
+/// Block indices visited during the timed phase, recorded only when
+/// --dump-sequence is given
+///
+/// This lets a random pattern that trips up a VFS be replayed exactly,
+/// without having to reproduce the generator that made it.
+pub struct Sequence {
+    ops: Option<Vec<(u64, Op)>>,
+}
+
+impl Sequence {
+    pub fn new(config: &Config, count: u64) -> Self {
+        Self {
+            ops: config.dump_sequence.as_ref()
+                .map(|_| Vec::with_capacity(usize::try_from(count).unwrap())),
+        }
+    }
+
+    /// Record an operation on the given block index, if enabled
+    pub fn record(&mut self, index: u64, op: Op) {
+        if let Some(ops) = &mut self.ops {
+            ops.push((index, op));
+        }
+    }
+
+    /// Write the recorded sequence as one `index op` per line, this should
+    /// only be called after the timed region
+    pub fn write(&self, config: &Config) {
+        let (path, ops) = match (&config.dump_sequence, &self.ops) {
+            (Some(path), Some(ops)) => (path, ops),
+            _ => return,
+        };
+
+        let mut file = BufWriter::new(File::create(path).unwrap());
+        for (index, op) in ops {
+            writeln!(file, "{} {}", index, op.name()).unwrap();
+        }
+        file.flush().unwrap();
+    }
+}
+
+/// Load a sequence written by --dump-sequence, or captured elsewhere in the
+/// same `index op` format, checking every index is below count
+///
+/// Blank lines are skipped, anything else malformed is an error naming the
+/// line, so a bad trace is never silently replayed in part.
+pub fn load(path: &str, count: u64) -> anyhow::Result<Vec<(u64, Op)>> {
+    let trace = fs::read_to_string(path)
+        .with_context(|| format!("Can't read trace {}", path))?;
+
+    let mut ops = Vec::new();
+    for (n, line) in trace.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let (index, op) = match line.split_whitespace().collect::<Vec<_>>()[..] {
+            [index, op] => (index, op),
+            _ => bail!("{}:{}: expected \"<index> <read|write>\", got {:?}", path, n+1, line),
+        };
+        let index = index.parse::<u64>()
+            .with_context(|| format!("{}:{}: can't parse index {:?}", path, n+1, index))?;
+        let op = op.parse::<Op>()
+            .with_context(|| format!("{}:{}: can't parse operation {:?}", path, n+1, op))?;
+        if index >= count {
+            bail!("{}:{}: index {} is out of range, the file only has {} blocks",
+                path, n+1, index, count);
+        }
+
+        ops.push((index, op));
+    }
+    Ok(ops)
+}
@@ -0,0 +1,44 @@
+//! Reading blocks with some context when a file is shorter than expected
+//!
+//! ## Authors
+//!
+//! The Veracruz Development Team.
+//!
+//! ## Copyright
+//!
+//! See the file `LICENSING.markdown` in the Veracruz root directory for licensing
+//! and copyright information.
+
+use crate::retry::with_retry;
+use std::{
+    fs::File,
+    io::ErrorKind,
+    io::Read,
+    process,
+};
+
+/// Fill buffer from the file's current position, which should be offset,
+/// retrying interrupted reads
+///
+/// A pre-created file can be short if an earlier run crashed part way
+/// through, and read_exact then just fails with UnexpectedEof. Instead this
+/// exits naming the block (or small file) index, how many bytes were
+/// expected, and how many were actually left in the file.
+pub fn read_block(
+    attempts: u32,
+    file: &mut File,
+    buffer: &mut [u8],
+    block: u64,
+    offset: u64,
+) {
+    let res = with_retry(attempts, || file.read_exact(buffer));
+    if let Err(err) = &res {
+        if err.kind() == ErrorKind::UnexpectedEof {
+            let len = file.metadata().unwrap().len();
+            eprintln!("Block {} is truncated, expected {} bytes at offset {} but only {} are available",
+                block, buffer.len(), offset, len.saturating_sub(offset));
+            process::exit(1);
+        }
+    }
+    res.unwrap()
+}
@@ -0,0 +1,81 @@
+//! Distributions of small file sizes
+//!
+//! ## Authors
+//!
+//! The Veracruz Development Team.
+//!
+//! ## Copyright
+//!
+//! See the file `LICENSING.markdown` in the Veracruz root directory for licensing
+//! and copyright information.
+
+use crate::{
+    Config,
+    rng::Prng,
+};
+use anyhow::bail;
+use std::{
+    convert::TryFrom,
+    str::FromStr,
+};
+
+/// How the sizes of small files are chosen
+///
+/// Real directories mix tiny and larger files, which a single block_size
+/// doesn't capture. Both distributions have a mean of block_size.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SizeDist {
+    /// every file is block_size bytes
+    Fixed,
+    /// sizes are uniform in 1..2*block_size
+    Uniform,
+    /// sizes are lognormal with a sigma of 1, a long tail of large files
+    Lognormal,
+}
+
+impl FromStr for SizeDist {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "fixed" => Ok(SizeDist::Fixed),
+            "uniform" => Ok(SizeDist::Uniform),
+            "lognormal" => Ok(SizeDist::Lognormal),
+            _ => bail!("Unknown size distribution {:?}, expected fixed, uniform or lognormal", s),
+        }
+    }
+}
+
+/// Size in bytes of the i'th small file
+///
+/// This only depends on the seed and the file's index, so the files
+/// created for reading and the files written by the benchmark agree.
+pub fn file_size(config: &Config, i: u64) -> usize {
+    let block_size = config.block_size;
+    if config.size_dist == SizeDist::Fixed {
+        return block_size;
+    }
+
+    // a different spread than the file contents use, so sizes and contents
+    // aren't correlated
+    let mut prng = Prng::new(
+        config.prng,
+        config.seed.wrapping_add(i).wrapping_mul(0xbf58476d1ce4e5b9) | 1,
+    );
+    // uniform in (0, 1), from the top 53 bits
+    let mut unit = || ((prng.next().unwrap() >> 11) as f64 + 0.5) / (1u64 << 53) as f64;
+
+    let size = match config.size_dist {
+        SizeDist::Fixed => unreachable!(),
+        SizeDist::Uniform => {
+            1.0 + (unit() * (2*block_size - 1) as f64).floor()
+        }
+        SizeDist::Lognormal => {
+            // Box-Muller, and exp(z - sigma²/2) has a mean of 1
+            let z = (-2.0 * unit().ln()).sqrt()
+                * (2.0 * std::f64::consts::PI * unit()).cos();
+            (block_size as f64 * (z - 0.5).exp()).round().max(1.0)
+        }
+    };
+    usize::try_from(size as u64).unwrap()
+}
@@ -9,62 +9,136 @@
 //! See the file `LICENSING.markdown` in the Veracruz root directory for licensing
 //! and copyright information.
 
+use crate::{
+    Config,
+    coverage::Coverage,
+    hint,
+    marker::mark,
+    file::warm_up,
+    report::Report,
+    report::supported,
+    retry::with_retry,
+    short_read::read_block,
+    scratch,
+    rng::Prng,
+    size_dist::{file_size, SizeDist},
+    threads::{timed_parallel, SetOnDrop},
+    interrupt::interrupted,
+};
 use std::{
     cell::RefCell,
     cmp::min,
+    collections::HashSet,
+    ffi::OsString,
     convert::TryFrom,
     fs,
     fs::File,
     fs::OpenOptions,
-    hint,
+    io,
+    io::ErrorKind,
     io::Write,
-    io::Read,
-    iter,
+    mem,
     ops::DerefMut,
+    sync::atomic::AtomicBool,
+    sync::atomic::Ordering,
+    thread,
     time::Duration,
     time::Instant,
+    time::SystemTime,
 };
 
-/// xorshift64 for providing deterministic pseudo-random numbers
-fn xorshift64(seed: u64) -> impl Iterator<Item=u64> {
-    let mut x = seed;
-    iter::repeat_with(move || {
-        x ^= x << 13;
-        x ^= x >> 7;
-        x ^= x << 17;
-        x
-    })
+/// Number of files per bucket of create latencies in small_create_many
+const CREATE_BUCKET: u64 = 10_000;
+
+/// First of the CJK ideographs unicode_names spells its names in, each is 3
+/// bytes of UTF-8, and 64 of them make a digit
+const NAME_ALPHABET: u32 = 0x4e00;
+
+/// Number of times list_churn lists its directory
+const LIST_PASSES: u32 = 16;
+
+
+/// Create and fill the size/block_size files that the update/read modes
+/// operate on, spread over config.setup_threads threads
+///
+/// Each file is filled from its own seed derived from its index, so the
+/// contents don't depend on how creation was split across threads. This
+/// phase is never timed.
+pub fn create_files(config: &Config, path: &str) {
+    let setup_threads = config.setup_threads;
+    timed_parallel(setup_threads, |t| {
+        create_files_strided(config, path, u64::from(t), u64::from(setup_threads))
+    });
+}
+
+/// Create and fill every step'th file starting at first
+fn create_files_strided(config: &Config, path: &str, first: u64, step: u64) {
+    let Config { size, block_size, retries, seed, .. } = *config;
+    let count = size/u64::try_from(block_size).unwrap();
+    let mut buffer = Vec::with_capacity(block_size);
+
+    for i in (first..count).step_by(usize::try_from(step).unwrap()) {
+        let path = format!("{}/{:09x}.txt", path, i);
+        buffer.resize(file_size(config, i), 0);
+
+        // spread neighbouring files' seeds apart, keeping them odd and so
+        // non-zero, which xorshift requires
+        let file_seed = seed.wrapping_add(i).wrapping_mul(0x9e3779b97f4a7c15) | 1;
+        for (x, y) in buffer.iter_mut().zip(Prng::new(config.prng, file_seed)) {
+            *x = y as u8;
+        }
+        mark(config, &mut buffer);
+
+        // curiously we need to open this file as read here to enable
+        // reading later, since the flags to open here affect the persistent
+        // capabilities on the filesystem
+        let mut file = with_retry(retries, || {
+            OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .open(&path)
+        }).unwrap();
+
+        with_retry(retries, || file.write_all(&buffer)).unwrap();
+
+        file.flush().unwrap();
+    }
 }
 
 
 /// Write small files in-order
-pub fn write_inorder(size: u64, block_size: usize, run: u32) -> Duration {
-    let path = format!("/scratch/small_write_inorder_{}_{}_{}", size, block_size, run);
-    let mut prng = xorshift64(42);
-    let mut buffer = vec![0u8; block_size];
-    fs::create_dir(&path).unwrap();
+pub fn write_inorder(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/small_write_inorder_{}_{}_{}", config.scratch_dir, size, block_size, run);
+    let mut prng = Prng::new(config.prng, seed);
+    let count = size/u64::try_from(block_size).unwrap();
+    let sizes = (0..count).map(|i| file_size(config, i)).collect::<Vec<_>>();
+    let mut buffer = vec![0u8; sizes.iter().copied().max().unwrap_or(0)];
+    scratch::create_dir(config, &path);
 
     let stopwatch = Instant::now();
 
-    for i in 0..size/u64::try_from(block_size).unwrap() {
+    for i in 0..count {
         let path = format!("{}/{:09x}.txt", path, i);
+        let file_size = sizes[usize::try_from(i).unwrap()];
 
         for (j, x) in
             (&mut prng)
-                .take(usize::try_from(
-                    min(i+u64::try_from(block_size).unwrap(), size) - i
-                ).unwrap())
+                .take(file_size)
                 .enumerate()
         {
             buffer[j] = x as u8;
         }
+        mark(config, &mut buffer[..file_size]);
         
         hint::black_box({
             let path = hint::black_box(&path);
-            let mut file = File::create(path).unwrap();
+            let mut file = with_retry(retries, || File::create(path)).unwrap();
 
-            let input = hint::black_box(&buffer);
-            file.write_all(input).unwrap();
+            let input = hint::black_box(&buffer[..file_size]);
+            with_retry(retries, || file.write_all(input)).unwrap();
 
             file.flush().unwrap();
         });
@@ -75,23 +149,29 @@ pub fn write_inorder(size: u64, block_size: usize, run: u32) -> Duration {
     // Clean up! Otherwise Veracruz may try to copy it back over
     // into the user's fs, which is a waste of (significant) time...
     //
-    for i in 0..size/u64::try_from(block_size).unwrap() {
+    for i in 0..count {
         let path = format!("{}/{:09x}.txt", path, i);
-        let file = File::create(path).unwrap();
+        let file = with_retry(retries, || File::create(&path)).unwrap();
         file.set_len(0).unwrap();
     }
 
-    duration
+    with_file_sizes(Report::new(duration), config, &sizes)
 }
 
 /// Update small files in-order
-pub fn update_inorder(size: u64, block_size: usize, run: u32) -> Duration {
-    let path = format!("/scratch/small_write_inorder_{}_{}_{}", size, block_size, run);
-    let mut prng = xorshift64(42);
+pub fn update_inorder(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/small_write_inorder_{}_{}_{}", config.scratch_dir, size, block_size, run);
+    let mut prng = Prng::new(config.prng, seed);
     let mut buffer = vec![0u8; block_size];
-    fs::create_dir(&path).unwrap();
+    scratch::create_dir(config, &path);
 
     // first create the files
+    create_files(config, &path);
+
+    // then benchmark
+    let stopwatch = Instant::now();
+
     for i in 0..size/u64::try_from(block_size).unwrap() {
         let path = format!("{}/{:09x}.txt", path, i);
 
@@ -104,52 +184,63 @@ pub fn update_inorder(size: u64, block_size: usize, run: u32) -> Duration {
         {
             buffer[j] = x as u8;
         }
+        mark(config, &mut buffer);
         
         hint::black_box({
-            // curiously we need to open this file as read here to enable
-            // reading later, since the flags to open here affect the persistent
-            // capabilities on the filesystem
             let path = hint::black_box(&path);
-            let mut file = OpenOptions::new()
-                .read(true)
-                .write(true)
-                .create(true)
-                .truncate(true)
-                .open(path).unwrap();
+            let mut file = with_retry(retries, || {
+                OpenOptions::new()
+                    .write(true)
+                    .open(path)
+            }).unwrap();
 
             let input = hint::black_box(&buffer);
-            file.write_all(input).unwrap();
+            with_retry(retries, || file.write_all(input)).unwrap();
 
             file.flush().unwrap();
         });
     }
 
-    // then benchmark
-    let stopwatch = Instant::now();
+    let duration = stopwatch.elapsed();
 
+    // Clean up! Otherwise Veracruz may try to copy it back over
+    // into the user's fs, which is a waste of (significant) time...
+    //
     for i in 0..size/u64::try_from(block_size).unwrap() {
         let path = format!("{}/{:09x}.txt", path, i);
+        let file = with_retry(retries, || File::create(&path)).unwrap();
+        file.set_len(0).unwrap();
+    }
 
-        for (j, x) in
-            (&mut prng)
-                .take(usize::try_from(
-                    min(i+u64::try_from(block_size).unwrap(), size) - i
-                ).unwrap())
-                .enumerate()
-        {
-            buffer[j] = x as u8;
-        }
+    Report::new(duration)
+}
+
+/// Read small files in-order
+pub fn read_inorder(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, .. } = *config;
+    let path = format!("{}/small_write_inorder_{}_{}_{}", config.scratch_dir, size, block_size, run);
+    let count = size/u64::try_from(block_size).unwrap();
+    let sizes = (0..count).map(|i| file_size(config, i)).collect::<Vec<_>>();
+    let mut buffer = vec![0u8; sizes.iter().copied().max().unwrap_or(0)];
+    scratch::create_dir(config, &path);
+
+    // first create the files
+    create_files(config, &path);
+
+    // then benchmark
+    let stopwatch = Instant::now();
+
+    for i in 0..count {
+        let path = format!("{}/{:09x}.txt", path, i);
+        let file_size = sizes[usize::try_from(i).unwrap()];
         
         hint::black_box({
             let path = hint::black_box(&path);
-            let mut file = OpenOptions::new()
-                .write(true)
-                .open(path).unwrap();
+            let mut file = with_retry(retries, || File::open(path)).unwrap();
 
-            let input = hint::black_box(&buffer);
-            file.write_all(input).unwrap();
-
-            file.flush().unwrap();
+            let buffer = &mut buffer[..file_size];
+            read_block(retries, &mut file, hint::black_box(&mut *buffer), i, 0);
+            &*buffer
         });
     }
 
@@ -158,172 +249,326 @@ pub fn update_inorder(size: u64, block_size: usize, run: u32) -> Duration {
     // Clean up! Otherwise Veracruz may try to copy it back over
     // into the user's fs, which is a waste of (significant) time...
     //
-    for i in 0..size/u64::try_from(block_size).unwrap() {
+    for i in 0..count {
         let path = format!("{}/{:09x}.txt", path, i);
-        let file = File::create(path).unwrap();
+        let file = with_retry(retries, || File::create(&path)).unwrap();
         file.set_len(0).unwrap();
     }
 
-    duration
+    with_file_sizes(Report::new(duration), config, &sizes)
 }
 
-/// Read small files in-order
-pub fn read_inorder(size: u64, block_size: usize, run: u32) -> Duration {
-    let path = format!("/scratch/small_write_inorder_{}_{}_{}", size, block_size, run);
-    let mut prng = xorshift64(42);
+/// Report the realized file sizes when they follow a --size-dist
+fn with_file_sizes(report: Report, config: &Config, sizes: &[usize]) -> Report {
+    if config.size_dist == SizeDist::Fixed || sizes.is_empty() {
+        return report;
+    }
+
+    let total = sizes.iter().map(|&size| u64::try_from(size).unwrap()).sum::<u64>();
+    report
+        .with("file_size_min", *sizes.iter().min().unwrap())
+        .with("file_size_mean", total as f64 / sizes.len() as f64)
+        .with("file_size_max", *sizes.iter().max().unwrap())
+        .with("file_bytes", total)
+}
+
+/// Stat each small file and then read it, as code checking a file's size
+/// before reading it does, timing the combined sequence
+///
+/// The stat and read durations are also reported separately. A second pass
+/// then reads every file again without a stat, as a control showing whether
+/// the stat warms anything that speeds up the read following it. It runs
+/// second so any caching left by the first pass favours the control, not
+/// the reads after a stat.
+pub fn stat_then_read(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, .. } = *config;
+    let path = format!("{}/stat_then_read_{}_{}_{}", config.scratch_dir, size, block_size, run);
     let mut buffer = vec![0u8; block_size];
-    fs::create_dir(&path).unwrap();
+    scratch::create_dir(config, &path);
 
     // first create the files
-    for i in 0..size/u64::try_from(block_size).unwrap() {
+    create_files(config, &path);
+
+    // then benchmark
+    let mut stat_duration = Duration::ZERO;
+    let mut read_duration = Duration::ZERO;
+    let stopwatch = Instant::now();
+
+    let count = size/u64::try_from(block_size).unwrap();
+    for i in 0..count {
         let path = format!("{}/{:09x}.txt", path, i);
 
-        for (j, x) in
-            (&mut prng)
-                .take(usize::try_from(
-                    min(i+u64::try_from(block_size).unwrap(), size) - i
-                ).unwrap())
-                .enumerate()
-        {
-            buffer[j] = x as u8;
-        }
-        
+        let stat_stopwatch = Instant::now();
         hint::black_box({
-            // curiously we need to open this file as read here to enable
-            // reading later, since the flags to open here affect the persistent
-            // capabilities on the filesystem
             let path = hint::black_box(&path);
-            let mut file = OpenOptions::new()
-                .read(true)
-                .write(true)
-                .create(true)
-                .truncate(true)
-                .open(path).unwrap();
+            with_retry(retries, || fs::metadata(path)).unwrap().len()
+        });
+        stat_duration += stat_stopwatch.elapsed();
 
-            let input = hint::black_box(&buffer);
-            file.write_all(input).unwrap();
+        let read_stopwatch = Instant::now();
+        hint::black_box({
+            let path = hint::black_box(&path);
+            let mut file = with_retry(retries, || File::open(path)).unwrap();
 
-            file.flush().unwrap();
+            read_block(retries, &mut file, hint::black_box(&mut buffer), i, 0);
+            &buffer
         });
+        read_duration += read_stopwatch.elapsed();
     }
 
-    // then benchmark
+    let duration = stopwatch.elapsed();
+
+    // and the control, reading without a stat
     let stopwatch = Instant::now();
 
-    for i in 0..size/u64::try_from(block_size).unwrap() {
+    for i in 0..count {
         let path = format!("{}/{:09x}.txt", path, i);
-        
+
         hint::black_box({
             let path = hint::black_box(&path);
-            let mut file = File::open(path).unwrap();
+            let mut file = with_retry(retries, || File::open(path)).unwrap();
 
-            file.read_exact(hint::black_box(&mut buffer)).unwrap();
+            read_block(retries, &mut file, hint::black_box(&mut buffer), i, 0);
             &buffer
         });
     }
 
-    let duration = stopwatch.elapsed();
+    let unstated_read_duration = stopwatch.elapsed();
 
     // Clean up! Otherwise Veracruz may try to copy it back over
     // into the user's fs, which is a waste of (significant) time...
     //
-    for i in 0..size/u64::try_from(block_size).unwrap() {
+    for i in 0..count {
         let path = format!("{}/{:09x}.txt", path, i);
-        let file = File::create(path).unwrap();
+        let file = with_retry(retries, || File::create(&path)).unwrap();
         file.set_len(0).unwrap();
     }
 
-    duration
+    Report::new(duration)
+        .with("files", count)
+        .with("stat_runtime", stat_duration.as_secs_f64())
+        .with("read_runtime", read_duration.as_secs_f64())
+        .with("unstated_read_runtime", unstated_read_duration.as_secs_f64())
+        .with("stat_read_speedup",
+            unstated_read_duration.as_secs_f64() / read_duration.as_secs_f64()
+        )
 }
 
-/// Write small files in reversed-order
-pub fn write_reversed(size: u64, block_size: usize, run: u32) -> Duration {
-    let path = format!("/scratch/small_write_reversed_{}_{}_{}", size, block_size, run);
-    let mut prng = xorshift64(42);
+/// Read only the first --read-fraction of each small file before closing
+/// it, compared against reading the files whole
+///
+/// This models reading just a header and giving up. If the VFS prefetches
+/// beyond what was asked for, the partial reads cost about as much as the
+/// whole ones, wasting work whenever reads are cut short.
+pub fn partial_read(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, read_fraction, .. } = *config;
+    let path = format!("{}/partial_read_{}_{}_{}", config.scratch_dir, size, block_size, run);
     let mut buffer = vec![0u8; block_size];
-    fs::create_dir(&path).unwrap();
+    scratch::create_dir(config, &path);
 
-    let stopwatch = Instant::now();
+    // first create the files
+    create_files(config, &path);
 
-    for i in (0..size/u64::try_from(block_size).unwrap()).rev() {
-        let path = format!("{}/{:09x}.txt", path, i);
+    // then measure reading a prefix of each, and then all of each
+    let partial = (block_size as f64 * read_fraction).ceil() as usize;
+    let count = size/u64::try_from(block_size).unwrap();
+    let mut read = |len: usize| {
+        let stopwatch = Instant::now();
 
-        for (j, x) in
-            (&mut prng)
-                .take(usize::try_from(
-                    min(i+u64::try_from(block_size).unwrap(), size) - i
-                ).unwrap())
-                .enumerate()
-        {
-            buffer[j] = x as u8;
-        }
-        
-        hint::black_box({
-            let path = hint::black_box(&path);
-            let mut file = File::create(path).unwrap();
+        for i in 0..count {
+            let path = format!("{}/{:09x}.txt", path, i);
 
-            let input = hint::black_box(&buffer);
-            file.write_all(input).unwrap();
+            hint::black_box({
+                let path = hint::black_box(&path);
+                let mut file = with_retry(retries, || File::open(path)).unwrap();
 
-            file.flush().unwrap();
-        });
-    }
+                read_block(retries, &mut file, hint::black_box(&mut buffer[..len]), i, 0);
+                &buffer
+            });
+        }
 
-    let duration = stopwatch.elapsed();
+        stopwatch.elapsed()
+    };
+    let duration = read(partial);
+    let full_duration = read(block_size);
 
     // Clean up! Otherwise Veracruz may try to copy it back over
     // into the user's fs, which is a waste of (significant) time...
     //
-    for i in 0..size/u64::try_from(block_size).unwrap() {
+    for i in 0..count {
         let path = format!("{}/{:09x}.txt", path, i);
-        let file = File::create(path).unwrap();
+        let file = with_retry(retries, || File::create(&path)).unwrap();
         file.set_len(0).unwrap();
     }
 
-    duration
+    Report::new(duration)
+        .with("read_fraction", read_fraction)
+        .with("bytes_read_per_file", partial)
+        .with("full_read_runtime", full_duration.as_secs_f64())
+        .with("partial_vs_full_ratio",
+            duration.as_secs_f64() / full_duration.as_secs_f64()
+        )
 }
 
-/// Update small files in reversed-order
-pub fn update_reversed(size: u64, block_size: usize, run: u32) -> Duration {
-    let path = format!("/scratch/small_write_reversed_{}_{}_{}", size, block_size, run);
-    let mut prng = xorshift64(42);
-    let mut buffer = vec![0u8; block_size];
-    fs::create_dir(&path).unwrap();
+/// Truncate small files by reopening them with File::create, then the
+/// same files, refilled, by opening them for writing and calling set_len(0)
+///
+/// File::create truncates as part of the open, so comparing the two shows
+/// whether the VFS's truncate-on-open path costs more or less than an
+/// explicit truncate. Only the opens and truncates are timed.
+pub fn truncate_compare(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, .. } = *config;
+    let path = format!("{}/truncate_compare_{}_{}_{}", config.scratch_dir, size, block_size, run);
+    scratch::create_dir(config, &path);
+    let count = size/u64::try_from(block_size).unwrap();
 
     // first create the files
-    for i in 0..size/u64::try_from(block_size).unwrap() {
+    create_files(config, &path);
+
+    // then measure truncating them on open
+    let stopwatch = Instant::now();
+
+    for i in 0..count {
         let path = format!("{}/{:09x}.txt", path, i);
 
-        for (j, x) in
-            (&mut prng)
-                .take(usize::try_from(
-                    min(i+u64::try_from(block_size).unwrap(), size) - i
-                ).unwrap())
-                .enumerate()
-        {
-            buffer[j] = x as u8;
-        }
-        
         hint::black_box({
-            // curiously we need to open this file as read here to enable
-            // reading later, since the flags to open here affect the persistent
-            // capabilities on the filesystem
             let path = hint::black_box(&path);
-            let mut file = OpenOptions::new()
-                .read(true)
-                .write(true)
-                .create(true)
-                .truncate(true)
-                .open(path).unwrap();
+            with_retry(retries, || File::create(path)).unwrap()
+        });
+    }
 
-            let input = hint::black_box(&buffer);
-            file.write_all(input).unwrap();
+    let duration = stopwatch.elapsed();
 
-            file.flush().unwrap();
+    // refill the files, and measure truncating them after opening
+    create_files(config, &path);
+    let stopwatch = Instant::now();
+
+    for i in 0..count {
+        let file_path = format!("{}/{:09x}.txt", path, i);
+
+        hint::black_box({
+            let file_path = hint::black_box(&file_path);
+            let file = with_retry(retries, || {
+                OpenOptions::new()
+                    .write(true)
+                    .open(file_path)
+            }).unwrap();
+            supported!(file.set_len(0), fs::remove_dir_all(&path).unwrap());
+            file
         });
     }
 
-    // then benchmark
+    let explicit_duration = stopwatch.elapsed();
+
+    // both passes should have left every file empty
+    let truncated = (0..count).all(|i| {
+        let path = format!("{}/{:09x}.txt", path, i);
+        fs::metadata(&path).map(|metadata| metadata.len() == 0).unwrap_or(false)
+    });
+
+    // the files were all truncated, so there's nothing left to clean up
+
+    Report::new(duration)
+        .with("files", count)
+        .with("explicit_truncate_runtime", explicit_duration.as_secs_f64())
+        .with("create_vs_explicit_ratio",
+            duration.as_secs_f64() / explicit_duration.as_secs_f64()
+        )
+        .with("truncated", truncated)
+}
+
+/// Set the modified time of each small file with File::set_modified, first
+/// in-order and then in random-order
+///
+/// Tools like `cp -p` and make set timestamps, which may take a slow
+/// metadata path on a VFS. Only the set_modified calls are timed, and each
+/// file's time is checked afterwards, counting any that didn't stick.
+pub fn set_mtime(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/set_mtime_{}_{}_{}", config.scratch_dir, size, block_size, run);
+    let mut prng = Prng::new(config.prng, seed);
+    scratch::create_dir(config, &path);
+    let count = size/u64::try_from(block_size).unwrap();
+
+    // first create the files
+    create_files(config, &path);
+
+    // each pass gives every file its own time, distinct from the last pass
+    let set = |order: &[u64], base: u64| {
+        let mut duration = Duration::ZERO;
+        for &i in order {
+            let path = format!("{}/{:09x}.txt", path, i);
+            let file = with_retry(retries, || {
+                OpenOptions::new()
+                    .write(true)
+                    .open(&path)
+            }).unwrap();
+            let time = SystemTime::UNIX_EPOCH + Duration::from_secs(base + i);
+
+            let stopwatch = Instant::now();
+            hint::black_box(
+                hint::black_box(&file).set_modified(hint::black_box(time))
+            )?;
+            duration += stopwatch.elapsed();
+        }
+        Ok::<_, io::Error>(duration)
+    };
+
+    // then measure setting times in-order, and in random-order
+    let inorder = (0..count).collect::<Vec<_>>();
+    let random = (0..count)
+        .map(|_| prng.next().unwrap() % count)
+        .collect::<Vec<_>>();
+    let duration = supported!(
+        set(&inorder, 1_000_000_000),
+        fs::remove_dir_all(&path).unwrap()
+    );
+    let random_duration = supported!(
+        set(&random, 1_500_000_000),
+        fs::remove_dir_all(&path).unwrap()
+    );
+
+    // every file should hold the time of the last pass to touch it
+    let mut latest = (0..count)
+        .map(|i| 1_000_000_000 + i)
+        .collect::<Vec<_>>();
+    for &i in &random {
+        latest[usize::try_from(i).unwrap()] = 1_500_000_000 + i;
+    }
+    let mismatched = u64::try_from((0..count).filter(|&i| {
+        let path = format!("{}/{:09x}.txt", path, i);
+        let expected = SystemTime::UNIX_EPOCH
+            + Duration::from_secs(latest[usize::try_from(i).unwrap()]);
+        fs::metadata(&path).and_then(|metadata| metadata.modified())
+            .map(|modified| modified != expected)
+            .unwrap_or(true)
+    }).count()).unwrap();
+
+    // Clean up! Otherwise Veracruz may try to copy it back over
+    // into the user's fs, which is a waste of (significant) time...
+    //
+    for i in 0..count {
+        let path = format!("{}/{:09x}.txt", path, i);
+        let file = with_retry(retries, || File::create(&path)).unwrap();
+        file.set_len(0).unwrap();
+    }
+
+    Report::new(duration)
+        .with("ops", count)
+        .with("ops_per_sec", count as f64 / duration.as_secs_f64())
+        .with("random_runtime", random_duration.as_secs_f64())
+        .with("random_ops_per_sec", count as f64 / random_duration.as_secs_f64())
+        .with("mismatched_times", mismatched)
+}
+
+/// Write small files in reversed-order
+pub fn write_reversed(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/small_write_reversed_{}_{}_{}", config.scratch_dir, size, block_size, run);
+    let mut prng = Prng::new(config.prng, seed);
+    let mut buffer = vec![0u8; block_size];
+    scratch::create_dir(config, &path);
+
     let stopwatch = Instant::now();
 
     for i in (0..size/u64::try_from(block_size).unwrap()).rev() {
@@ -338,15 +583,14 @@ pub fn update_reversed(size: u64, block_size: usize, run: u32) -> Duration {
         {
             buffer[j] = x as u8;
         }
+        mark(config, &mut buffer);
         
         hint::black_box({
             let path = hint::black_box(&path);
-            let mut file = OpenOptions::new()
-                .write(true)
-                .open(path).unwrap();
+            let mut file = with_retry(retries, || File::create(path)).unwrap();
 
             let input = hint::black_box(&buffer);
-            file.write_all(input).unwrap();
+            with_retry(retries, || file.write_all(input)).unwrap();
 
             file.flush().unwrap();
         });
@@ -359,22 +603,28 @@ pub fn update_reversed(size: u64, block_size: usize, run: u32) -> Duration {
     //
     for i in 0..size/u64::try_from(block_size).unwrap() {
         let path = format!("{}/{:09x}.txt", path, i);
-        let file = File::create(path).unwrap();
+        let file = with_retry(retries, || File::create(&path)).unwrap();
         file.set_len(0).unwrap();
     }
 
-    duration
+    Report::new(duration)
 }
 
-/// Read small files in reversed-order
-pub fn read_reversed(size: u64, block_size: usize, run: u32) -> Duration {
-    let path = format!("/scratch/small_write_reversed_{}_{}_{}", size, block_size, run);
-    let mut prng = xorshift64(42);
+/// Update small files in reversed-order
+pub fn update_reversed(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/small_write_reversed_{}_{}_{}", config.scratch_dir, size, block_size, run);
+    let mut prng = Prng::new(config.prng, seed);
     let mut buffer = vec![0u8; block_size];
-    fs::create_dir(&path).unwrap();
+    scratch::create_dir(config, &path);
 
     // first create the files
-    for i in 0..size/u64::try_from(block_size).unwrap() {
+    create_files(config, &path);
+
+    // then benchmark
+    let stopwatch = Instant::now();
+
+    for i in (0..size/u64::try_from(block_size).unwrap()).rev() {
         let path = format!("{}/{:09x}.txt", path, i);
 
         for (j, x) in
@@ -386,26 +636,47 @@ pub fn read_reversed(size: u64, block_size: usize, run: u32) -> Duration {
         {
             buffer[j] = x as u8;
         }
+        mark(config, &mut buffer);
         
         hint::black_box({
-            // curiously we need to open this file as read here to enable
-            // reading later, since the flags to open here affect the persistent
-            // capabilities on the filesystem
             let path = hint::black_box(&path);
-            let mut file = OpenOptions::new()
-                .read(true)
-                .write(true)
-                .create(true)
-                .truncate(true)
-                .open(path).unwrap();
+            let mut file = with_retry(retries, || {
+                OpenOptions::new()
+                    .write(true)
+                    .open(path)
+            }).unwrap();
 
             let input = hint::black_box(&buffer);
-            file.write_all(input).unwrap();
+            with_retry(retries, || file.write_all(input)).unwrap();
 
             file.flush().unwrap();
         });
     }
 
+    let duration = stopwatch.elapsed();
+
+    // Clean up! Otherwise Veracruz may try to copy it back over
+    // into the user's fs, which is a waste of (significant) time...
+    //
+    for i in 0..size/u64::try_from(block_size).unwrap() {
+        let path = format!("{}/{:09x}.txt", path, i);
+        let file = with_retry(retries, || File::create(&path)).unwrap();
+        file.set_len(0).unwrap();
+    }
+
+    Report::new(duration)
+}
+
+/// Read small files in reversed-order
+pub fn read_reversed(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, .. } = *config;
+    let path = format!("{}/small_write_reversed_{}_{}_{}", config.scratch_dir, size, block_size, run);
+    let mut buffer = vec![0u8; block_size];
+    scratch::create_dir(config, &path);
+
+    // first create the files
+    create_files(config, &path);
+
     // then benchmark
     let stopwatch = Instant::now();
 
@@ -414,9 +685,9 @@ pub fn read_reversed(size: u64, block_size: usize, run: u32) -> Duration {
         
         hint::black_box({
             let path = hint::black_box(&path);
-            let mut file = File::open(path).unwrap();
+            let mut file = with_retry(retries, || File::open(path)).unwrap();
 
-            file.read_exact(hint::black_box(&mut buffer)).unwrap();
+            read_block(retries, &mut file, hint::black_box(&mut buffer), i, 0);
             &buffer
         });
     }
@@ -428,27 +699,30 @@ pub fn read_reversed(size: u64, block_size: usize, run: u32) -> Duration {
     //
     for i in 0..size/u64::try_from(block_size).unwrap() {
         let path = format!("{}/{:09x}.txt", path, i);
-        let file = File::create(path).unwrap();
+        let file = with_retry(retries, || File::create(&path)).unwrap();
         file.set_len(0).unwrap();
     }
 
-    duration
+    Report::new(duration)
 }
 
 /// Write small files in random-order
-pub fn write_random(size: u64, block_size: usize, run: u32) -> Duration {
-    let path = format!("/scratch/small_write_random_{}_{}_{}", size, block_size, run);
-    let prng = RefCell::new(xorshift64(42));
+pub fn write_random(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/small_write_random_{}_{}_{}", config.scratch_dir, size, block_size, run);
+    let prng = RefCell::new(Prng::new(config.prng, seed));
     let mut buffer = vec![0u8; block_size];
-    fs::create_dir(&path).unwrap();
+    scratch::create_dir(config, &path);
 
     let stopwatch = Instant::now();
 
     let count = size/u64::try_from(block_size).unwrap();
+    let mut coverage = Coverage::new(count);
     for i in 
         (0..count)
             .map(|_| prng.borrow_mut().next().unwrap() % count)
     {
+        coverage.touch(i);
         let path = format!("{}/{:09x}.txt", path, i);
 
         for (j, x) in
@@ -462,13 +736,14 @@ pub fn write_random(size: u64, block_size: usize, run: u32) -> Duration {
         {
             buffer[j] = x as u8;
         }
+        mark(config, &mut buffer);
         
         hint::black_box({
             let path = hint::black_box(&path);
-            let mut file = File::create(path).unwrap();
+            let mut file = with_retry(retries, || File::create(path)).unwrap();
 
             let input = hint::black_box(&buffer);
-            file.write_all(input).unwrap();
+            with_retry(retries, || file.write_all(input)).unwrap();
 
             file.flush().unwrap();
         });
@@ -481,63 +756,35 @@ pub fn write_random(size: u64, block_size: usize, run: u32) -> Duration {
     //
     for i in 0..size/u64::try_from(block_size).unwrap() {
         let path = format!("{}/{:09x}.txt", path, i);
-        let file = File::create(path).unwrap();
+        let file = with_retry(retries, || File::create(&path)).unwrap();
         file.set_len(0).unwrap();
     }
 
-    duration
+    Report::new(duration)
+        .with_coverage(&coverage)
 }
 
 /// Update small files in random-order
-pub fn update_random(size: u64, block_size: usize, run: u32) -> Duration {
-    let path = format!("/scratch/small_write_random_{}_{}_{}", size, block_size, run);
-    let prng = RefCell::new(xorshift64(42));
+pub fn update_random(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/small_write_random_{}_{}_{}", config.scratch_dir, size, block_size, run);
+    let prng = RefCell::new(Prng::new(config.prng, seed));
     let mut buffer = vec![0u8; block_size];
-    fs::create_dir(&path).unwrap();
+    scratch::create_dir(config, &path);
 
     // first create the files
-    for i in 0..size/u64::try_from(block_size).unwrap() {
-        let path = format!("{}/{:09x}.txt", path, i);
-
-        for (j, x) in
-            prng
-                .borrow_mut()
-                .deref_mut()
-                .take(usize::try_from(
-                    min(i+u64::try_from(block_size).unwrap(), size) - i
-                ).unwrap())
-                .enumerate()
-        {
-            buffer[j] = x as u8;
-        }
-        
-        hint::black_box({
-            // curiously we need to open this file as read here to enable
-            // reading later, since the flags to open here affect the persistent
-            // capabilities on the filesystem
-            let path = hint::black_box(&path);
-            let mut file = OpenOptions::new()
-                .read(true)
-                .write(true)
-                .create(true)
-                .truncate(true)
-                .open(path).unwrap();
-
-            let input = hint::black_box(&buffer);
-            file.write_all(input).unwrap();
-
-            file.flush().unwrap();
-        });
-    }
+    create_files(config, &path);
 
     // then benchmark
     let stopwatch = Instant::now();
 
     let count = size/u64::try_from(block_size).unwrap();
+    let mut coverage = Coverage::new(count);
     for i in 
         (0..count)
             .map(|_| prng.borrow_mut().next().unwrap() % count)
     {
+        coverage.touch(i);
         let path = format!("{}/{:09x}.txt", path, i);
 
         for (j, x) in
@@ -551,15 +798,18 @@ pub fn update_random(size: u64, block_size: usize, run: u32) -> Duration {
         {
             buffer[j] = x as u8;
         }
+        mark(config, &mut buffer);
         
         hint::black_box({
             let path = hint::black_box(&path);
-            let mut file = OpenOptions::new()
-                .write(true)
-                .open(path).unwrap();
+            let mut file = with_retry(retries, || {
+                OpenOptions::new()
+                    .write(true)
+                    .open(path)
+            }).unwrap();
 
             let input = hint::black_box(&buffer);
-            file.write_all(input).unwrap();
+            with_retry(retries, || file.write_all(input)).unwrap();
 
             file.flush().unwrap();
         });
@@ -572,82 +822,984 @@ pub fn update_random(size: u64, block_size: usize, run: u32) -> Duration {
     //
     for i in 0..size/u64::try_from(block_size).unwrap() {
         let path = format!("{}/{:09x}.txt", path, i);
-        let file = File::create(path).unwrap();
+        let file = with_retry(retries, || File::create(&path)).unwrap();
         file.set_len(0).unwrap();
     }
 
-    duration
+    Report::new(duration)
+        .with_coverage(&coverage)
 }
 
 /// Read small files in random-order
-pub fn read_random(size: u64, block_size: usize, run: u32) -> Duration {
-    let path = format!("/scratch/small_write_random_{}_{}_{}", size, block_size, run);
-    let mut prng = xorshift64(42);
+pub fn read_random(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, warmup, .. } = *config;
+    let path = format!("{}/small_write_random_{}_{}_{}", config.scratch_dir, size, block_size, run);
+    let mut prng = Prng::new(config.prng, seed);
     let mut buffer = vec![0u8; block_size];
-    fs::create_dir(&path).unwrap();
+    scratch::create_dir(config, &path);
 
     // first create the files
-    for i in 0..size/u64::try_from(block_size).unwrap() {
-        let path = format!("{}/{:09x}.txt", path, i);
+    create_files(config, &path);
+
+    // optionally read everything once first, so we measure warm reads
+    if warmup {
+        for i in 0..size/u64::try_from(block_size).unwrap() {
+            let path = format!("{}/{:09x}.txt", path, i);
+            let mut file = with_retry(retries, || File::open(&path)).unwrap();
+            warm_up(&mut file, &mut buffer, retries);
+        }
+    }
 
-        for (j, x) in
-            (&mut prng)
-                .take(usize::try_from(
-                    min(i+u64::try_from(block_size).unwrap(), size) - i
-                ).unwrap())
-                .enumerate()
-        {
-            buffer[j] = x as u8;
-        }
+    // then benchmark
+    let stopwatch = Instant::now();
+
+    let count = size/u64::try_from(block_size).unwrap();
+    let mut coverage = Coverage::new(count);
+    for i in 
+        (0..count)
+            .map(|_| prng.next().unwrap() % count)
+    {
+        coverage.touch(i);
+        let path = format!("{}/{:09x}.txt", path, i);
         
         hint::black_box({
-            // curiously we need to open this file as read here to enable
-            // reading later, since the flags to open here affect the persistent
-            // capabilities on the filesystem
             let path = hint::black_box(&path);
-            let mut file = OpenOptions::new()
-                .read(true)
-                .write(true)
-                .create(true)
-                .truncate(true)
-                .open(path).unwrap();
+            let mut file = with_retry(retries, || File::open(path)).unwrap();
+
+            read_block(retries, &mut file, hint::black_box(&mut buffer), i, 0);
+            &buffer
+        });
+    }
+
+    let duration = stopwatch.elapsed();
+
+    // Clean up! Otherwise Veracruz may try to copy it back over
+    // into the user's fs, which is a waste of (significant) time...
+    //
+    for i in 0..size/u64::try_from(block_size).unwrap() {
+        let path = format!("{}/{:09x}.txt", path, i);
+        let file = with_retry(retries, || File::create(&path)).unwrap();
+        file.set_len(0).unwrap();
+    }
+
+    Report::new(duration)
+        .with_coverage(&coverage)
+        .with("warmup", warmup)
+}
+
+/// Move small files from one directory into another
+///
+/// Renaming within a directory is often pure metadata, moving between
+/// directories touches two parents, this measures the latter.
+pub fn rename_crossdir(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, .. } = *config;
+    let path = format!("{}/small_rename_crossdir_{}_{}_{}", config.scratch_dir, size, block_size, run);
+    let src = format!("{}/a", path);
+    let dst = format!("{}/b", path);
+    scratch::create_dir(config, &path);
+    fs::create_dir(&src).unwrap();
+    fs::create_dir(&dst).unwrap();
+
+    // first create the files
+    create_files(config, &src);
+
+    // then benchmark
+    let stopwatch = Instant::now();
+
+    let count = size/u64::try_from(block_size).unwrap();
+    for i in 0..count {
+        let from = format!("{}/{:09x}.txt", src, i);
+        let to = format!("{}/{:09x}.txt", dst, i);
+
+        hint::black_box({
+            let from = hint::black_box(&from);
+            supported!(
+                with_retry(retries, || fs::rename(from, &to)),
+                fs::remove_dir_all(&path).unwrap()
+            );
+        });
+    }
+
+    let duration = stopwatch.elapsed();
+
+    // Clean up! Otherwise Veracruz may try to copy it back over
+    // into the user's fs, which is a waste of (significant) time...
+    //
+    for i in 0..count {
+        let path = format!("{}/{:09x}.txt", dst, i);
+        let file = with_retry(retries, || File::create(&path)).unwrap();
+        file.set_len(0).unwrap();
+    }
+
+    Report::new(duration)
+        .with("renames", count)
+        .with("renames_per_sec", count as f64 / duration.as_secs_f64())
+}
+
+/// Write small files in-order with fs::write, compared against writing the
+/// same files through File::create and write_all
+///
+/// fs::write is what most simple programs call, and it may open and
+/// allocate differently from the File-based path.
+pub fn fs_write_inorder(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/small_fs_write_inorder_{}_{}_{}", config.scratch_dir, size, block_size, run);
+    let mut prng = Prng::new(config.prng, seed);
+    let mut buffer = vec![0u8; block_size];
+
+    // each approach gets a directory of its own, so both create new files
+    // rather than one overwriting the other's
+    let fs_path = format!("{}/fs", path);
+    let file_path = format!("{}/file", path);
+    scratch::create_dir(config, &path);
+    fs::create_dir(&fs_path).unwrap();
+    fs::create_dir(&file_path).unwrap();
+
+    // first measure fs::write
+    let stopwatch = Instant::now();
+
+    let count = size/u64::try_from(block_size).unwrap();
+    for i in 0..count {
+        let path = format!("{}/{:09x}.txt", fs_path, i);
+
+        for (x, y) in buffer.iter_mut().zip(&mut prng) {
+            *x = y as u8;
+        }
+        mark(config, &mut buffer);
+
+        hint::black_box({
+            let path = hint::black_box(&path);
+            let input = hint::black_box(&buffer);
+            with_retry(retries, || fs::write(path, input)).unwrap();
+        });
+    }
+
+    let fs_duration = stopwatch.elapsed();
+
+    // then the same files through File
+    let stopwatch = Instant::now();
+
+    for i in 0..count {
+        let path = format!("{}/{:09x}.txt", file_path, i);
+
+        for (x, y) in buffer.iter_mut().zip(&mut prng) {
+            *x = y as u8;
+        }
+        mark(config, &mut buffer);
+
+        hint::black_box({
+            let path = hint::black_box(&path);
+            let mut file = with_retry(retries, || File::create(path)).unwrap();
 
             let input = hint::black_box(&buffer);
-            file.write_all(input).unwrap();
+            with_retry(retries, || file.write_all(input)).unwrap();
 
             file.flush().unwrap();
         });
     }
 
-    // then benchmark
+    let file_duration = stopwatch.elapsed();
+
+    // Clean up! Otherwise Veracruz may try to copy it back over
+    // into the user's fs, which is a waste of (significant) time...
+    //
+    for path in [&fs_path, &file_path] {
+        for i in 0..count {
+            let path = format!("{}/{:09x}.txt", path, i);
+            let file = with_retry(retries, || File::create(&path)).unwrap();
+            file.set_len(0).unwrap();
+        }
+    }
+
+    Report::new(fs_duration)
+        .with("fs_runtime", fs_duration.as_secs_f64())
+        .with("file_runtime", file_duration.as_secs_f64())
+        .with("convenience_ratio",
+            fs_duration.as_secs_f64() / file_duration.as_secs_f64()
+        )
+}
+
+/// Read small files in-order with fs::read, compared against reading the
+/// same files through File::open and read_exact
+///
+/// fs::read allocates a fresh buffer sized from the file's metadata on every
+/// call, where the File-based path reuses one buffer.
+pub fn fs_read_inorder(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, .. } = *config;
+    let path = format!("{}/small_fs_read_inorder_{}_{}_{}", config.scratch_dir, size, block_size, run);
+    let mut buffer = vec![0u8; block_size];
+    scratch::create_dir(config, &path);
+
+    // first create the files
+    create_files(config, &path);
+
+    // then measure fs::read
     let stopwatch = Instant::now();
 
     let count = size/u64::try_from(block_size).unwrap();
-    for i in 
-        (0..count)
-            .map(|_| (&mut prng).next().unwrap() % count)
-    {
+    for i in 0..count {
         let path = format!("{}/{:09x}.txt", path, i);
-        
+
         hint::black_box({
             let path = hint::black_box(&path);
-            let mut file = File::open(path).unwrap();
+            with_retry(retries, || fs::read(path)).unwrap()
+        });
+    }
+
+    let fs_duration = stopwatch.elapsed();
+
+    // then the same files through File
+    let stopwatch = Instant::now();
 
-            file.read_exact(hint::black_box(&mut buffer)).unwrap();
+    for i in 0..count {
+        let path = format!("{}/{:09x}.txt", path, i);
+
+        hint::black_box({
+            let path = hint::black_box(&path);
+            let mut file = with_retry(retries, || File::open(path)).unwrap();
+
+            read_block(retries, &mut file, hint::black_box(&mut buffer), i, 0);
             &buffer
         });
     }
 
-    let duration = stopwatch.elapsed();
+    let file_duration = stopwatch.elapsed();
 
     // Clean up! Otherwise Veracruz may try to copy it back over
     // into the user's fs, which is a waste of (significant) time...
     //
-    for i in 0..size/u64::try_from(block_size).unwrap() {
+    for i in 0..count {
         let path = format!("{}/{:09x}.txt", path, i);
-        let file = File::create(path).unwrap();
+        let file = with_retry(retries, || File::create(&path)).unwrap();
         file.set_len(0).unwrap();
     }
 
-    duration
+    Report::new(fs_duration)
+        .with("fs_runtime", fs_duration.as_secs_f64())
+        .with("file_runtime", file_duration.as_secs_f64())
+        .with("convenience_ratio",
+            fs_duration.as_secs_f64() / file_duration.as_secs_f64()
+        )
+}
+
+/// Create small files from many threads at once, all in one shared
+/// directory, compared against each thread using a directory of its own
+///
+/// The difference shows how much creates contend on the directory in the
+/// VFS. The files are created empty, as only creation is measured.
+pub fn create_contention(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, threads, .. } = *config;
+    let path = format!("{}/small_create_contention_{}_{}_{}", config.scratch_dir, size, block_size, run);
+    let shared = format!("{}/shared", path);
+    scratch::create_dir(config, &path);
+    fs::create_dir(&shared).unwrap();
+    for t in 0..threads {
+        fs::create_dir(format!("{}/private_{}", path, t)).unwrap();
+    }
+
+    // each thread creates every threads'th file
+    let count = size/u64::try_from(block_size).unwrap();
+    let create = |dir: &str, t: u32| {
+        for i in (u64::from(t)..count).step_by(usize::try_from(threads).unwrap()) {
+            let path = format!("{}/{:09x}.txt", dir, i);
+
+            hint::black_box({
+                let path = hint::black_box(&path);
+                with_retry(retries, || File::create(path)).unwrap()
+            });
+        }
+    };
+
+    // first measure creates in the shared directory
+    let shared_duration = timed_parallel(threads, |t| create(&shared, t));
+
+    // then in a directory per thread
+    let private_duration = timed_parallel(threads, |t| {
+        create(&format!("{}/private_{}", path, t), t)
+    });
+
+    // the files are left empty, so there's nothing to truncate here
+
+    Report::new(shared_duration)
+        .with("threads", threads)
+        .with("creates", count)
+        .with("shared_creates_per_sec", count as f64 / shared_duration.as_secs_f64())
+        .with("private_runtime", private_duration.as_secs_f64())
+        .with("private_creates_per_sec", count as f64 / private_duration.as_secs_f64())
+        .with("contention_ratio",
+            shared_duration.as_secs_f64() / private_duration.as_secs_f64()
+        )
+}
+
+/// Create size/block_size empty files with long multi-byte UTF-8 names,
+/// then reopen each by name, at name lengths doubling from 8 characters up
+/// to --name-len
+///
+/// The other modes' short hex names never exercise how names are stored
+/// and compared. Each length gets a directory of its own, and the mean
+/// create and open latencies at each are reported. Opens that fail to find
+/// a file by its name are counted.
+pub fn unicode_names(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, name_len, .. } = *config;
+    let path = format!("{}/unicode_names_{}_{}_{}", config.scratch_dir, size, block_size, run);
+    scratch::create_dir(config, &path);
+    let count = size/u64::try_from(block_size).unwrap();
+
+    let mut lengths = vec![min(8, name_len)];
+    while *lengths.last().unwrap() < name_len {
+        lengths.push(min(2*lengths.last().unwrap(), name_len));
+    }
+
+    let mut create_latencies = Vec::new();
+    let mut open_latencies = Vec::new();
+    let mut duration = Duration::ZERO;
+    let mut reopen_failures = 0u64;
+
+    for &len in &lengths {
+        let dir = format!("{}/{}", path, len);
+        fs::create_dir(&dir).unwrap();
+        let names = (0..count)
+            .map(|i| format!("{}/{}", dir, unicode_name(i, len)))
+            .collect::<Vec<_>>();
+
+        // first measure creating the files
+        let stopwatch = Instant::now();
+
+        for name in &names {
+            hint::black_box({
+                let name = hint::black_box(name);
+                with_retry(retries, || File::create(name)).unwrap()
+            });
+        }
+
+        let create_duration = stopwatch.elapsed();
+
+        // then reopening them by the same names
+        let stopwatch = Instant::now();
+
+        for name in &names {
+            let res = hint::black_box({
+                let name = hint::black_box(name);
+                with_retry(retries, || File::open(name))
+            });
+            if res.is_err() {
+                reopen_failures += 1;
+            }
+        }
+
+        let open_duration = stopwatch.elapsed();
+
+        duration += create_duration + open_duration;
+        if count > 0 {
+            let count = u32::try_from(count).unwrap();
+            create_latencies.push((create_duration / count).as_secs_f64());
+            open_latencies.push((open_duration / count).as_secs_f64());
+        }
+    }
+
+    // the files are left empty, so there's nothing to truncate here
+
+    Report::new(duration)
+        .with("files_per_length", count)
+        .with("name_lengths", lengths)
+        .with("create_by_length", create_latencies)
+        .with("open_by_length", open_latencies)
+        .with("reopen_failures", reopen_failures)
+}
+
+/// A name of len characters from NAME_ALPHABET, spelling i in base 64 so
+/// every index gets its own name
+fn unicode_name(i: u64, len: usize) -> String {
+    (0..len)
+        .map(|digit| {
+            let digit = u32::try_from(i.checked_shr(6*digit as u32).unwrap_or(0) % 64).unwrap();
+            char::from_u32(NAME_ALPHABET + digit).unwrap()
+        })
+        .collect()
+}
+
+/// Create small files with create_new (O_EXCL), compared against the plain
+/// File::create used by small_write_inorder
+///
+/// An exclusive create must check the name doesn't exist, which may cost
+/// more. Only the creates are measured, the files are left empty.
+pub fn create_new(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, .. } = *config;
+    let path = format!("{}/small_create_new_{}_{}_{}", config.scratch_dir, size, block_size, run);
+    let excl_path = format!("{}/create_new", path);
+    let plain_path = format!("{}/create", path);
+    scratch::create_dir(config, &path);
+    fs::create_dir(&excl_path).unwrap();
+    fs::create_dir(&plain_path).unwrap();
+
+    // first measure exclusive creates
+    let stopwatch = Instant::now();
+
+    let count = size/u64::try_from(block_size).unwrap();
+    for i in 0..count {
+        let path = format!("{}/{:09x}.txt", excl_path, i);
+
+        hint::black_box({
+            let path = hint::black_box(&path);
+            with_retry(retries, || {
+                OpenOptions::new()
+                    .write(true)
+                    .create_new(true)
+                    .open(path)
+            }).unwrap()
+        });
+    }
+
+    let excl_duration = stopwatch.elapsed();
+
+    // then plain creates
+    let stopwatch = Instant::now();
+
+    for i in 0..count {
+        let path = format!("{}/{:09x}.txt", plain_path, i);
+
+        hint::black_box({
+            let path = hint::black_box(&path);
+            with_retry(retries, || File::create(path)).unwrap()
+        });
+    }
+
+    let plain_duration = stopwatch.elapsed();
+
+    // the files are empty, but remove them anyway so a rerun starts from
+    // empty directories
+    for path in [&excl_path, &plain_path] {
+        for i in 0..count {
+            fs::remove_file(format!("{}/{:09x}.txt", path, i)).unwrap();
+        }
+        fs::remove_dir(path).unwrap();
+    }
+    fs::remove_dir(&path).unwrap();
+
+    Report::new(excl_duration)
+        .with("creates", count)
+        .with("create_runtime", plain_duration.as_secs_f64())
+        .with("create_new_overhead",
+            (excl_duration.as_secs_f64() - plain_duration.as_secs_f64()) / count as f64
+        )
+}
+
+/// Repeatedly create a directory and write --files-per-dir small files
+/// into it, timing the whole interleaved sequence
+///
+/// This models ingesting a dataset while creating its directories on the
+/// fly, there are size/block_size/files_per_dir directories.
+pub fn mkdir_then_write(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, files_per_dir, .. } = *config;
+    let path = format!("{}/mkdir_then_write_{}_{}_{}", config.scratch_dir, size, block_size, run);
+    let mut prng = Prng::new(config.prng, seed);
+    let mut buffer = vec![0u8; block_size];
+    scratch::create_dir(config, &path);
+
+    let dirs = size/u64::try_from(block_size).unwrap()/files_per_dir;
+    let stopwatch = Instant::now();
+
+    for i in 0..dirs {
+        let dir = format!("{}/{:09x}", path, i);
+
+        hint::black_box({
+            let dir = hint::black_box(&dir);
+            with_retry(retries, || fs::create_dir(dir)).unwrap();
+        });
+
+        for j in 0..files_per_dir {
+            let path = format!("{}/{:09x}.txt", dir, j);
+
+            for (x, y) in buffer.iter_mut().zip(&mut prng) {
+                *x = y as u8;
+            }
+            mark(config, &mut buffer);
+
+            hint::black_box({
+                let path = hint::black_box(&path);
+                let mut file = with_retry(retries, || File::create(path)).unwrap();
+
+                let input = hint::black_box(&buffer);
+                with_retry(retries, || file.write_all(input)).unwrap();
+
+                file.flush().unwrap();
+            });
+        }
+    }
+
+    let duration = stopwatch.elapsed();
+
+    // Clean up the whole tree! Otherwise Veracruz may try to copy it back
+    // over into the user's fs, which is a waste of (significant) time...
+    //
+    for i in 0..dirs {
+        let dir = format!("{}/{:09x}", path, i);
+        for j in 0..files_per_dir {
+            fs::remove_file(format!("{}/{:09x}.txt", dir, j)).unwrap();
+        }
+        fs::remove_dir(&dir).unwrap();
+    }
+    fs::remove_dir(&path).unwrap();
+
+    Report::new(duration)
+        .with("files_per_dir", files_per_dir)
+        .with("dirs_created", dirs)
+        .with("files_created", dirs*files_per_dir)
+}
+
+/// Repeatedly create a small file, write it and delete it again, timing
+/// the whole cycle
+///
+/// This is the pattern temp-file-heavy programs generate, allocating and
+/// freeing an inode in tight succession. There are size/block_size cycles,
+/// each on a new name.
+pub fn churn(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/churn_{}_{}_{}", config.scratch_dir, size, block_size, run);
+    let mut prng = Prng::new(config.prng, seed);
+    let mut buffer = vec![0u8; block_size];
+    scratch::create_dir(config, &path);
+
+    let count = size/u64::try_from(block_size).unwrap();
+    let stopwatch = Instant::now();
+
+    for i in 0..count {
+        let path = format!("{}/{:09x}.txt", path, i);
+
+        for (x, y) in buffer.iter_mut().zip(&mut prng) {
+            *x = y as u8;
+        }
+        mark(config, &mut buffer);
+
+        hint::black_box({
+            let path = hint::black_box(&path);
+            let mut file = with_retry(retries, || File::create(path)).unwrap();
+
+            let input = hint::black_box(&buffer);
+            with_retry(retries, || file.write_all(input)).unwrap();
+
+            file.flush().unwrap();
+            mem::drop(file);
+
+            with_retry(retries, || fs::remove_file(path)).unwrap();
+        });
+    }
+
+    let duration = stopwatch.elapsed();
+
+    // every file is already deleted, only the directory is left
+    fs::remove_dir(&path).unwrap();
+
+    Report::new(duration)
+        .with("cycles", count)
+        .with("cycles_per_sec", count as f64 / duration.as_secs_f64())
+}
+
+/// Keep a directory at a steady --population files, each step creating a
+/// new small file and deleting the oldest, for size/block_size steps
+///
+/// This models a cache directory, recycling inodes one at a time rather
+/// than in bulk as churn does. The directory is filled to the population
+/// first, untimed, so every step is steady-state.
+pub fn steady_population(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, population, .. } = *config;
+    let path = format!("{}/steady_population_{}_{}_{}", config.scratch_dir, size, block_size, run);
+    let mut prng = Prng::new(config.prng, seed);
+    let mut buffer = vec![0u8; block_size];
+    scratch::create_dir(config, &path);
+
+    let mut create = |i: u64| {
+        let path = format!("{}/{:09x}.txt", path, i);
+
+        for (x, y) in buffer.iter_mut().zip(&mut prng) {
+            *x = y as u8;
+        }
+        mark(config, &mut buffer);
+
+        hint::black_box({
+            let path = hint::black_box(&path);
+            let mut file = with_retry(retries, || File::create(path)).unwrap();
+
+            let input = hint::black_box(&buffer);
+            with_retry(retries, || file.write_all(input)).unwrap();
+
+            file.flush().unwrap();
+        });
+    };
+
+    // first fill the directory
+    for i in 0..population {
+        create(i);
+    }
+
+    // then measure replacing the oldest file with a new one
+    let count = size/u64::try_from(block_size).unwrap();
+    let stopwatch = Instant::now();
+
+    for i in 0..count {
+        create(population + i);
+
+        hint::black_box({
+            let path = hint::black_box(format!("{}/{:09x}.txt", path, i));
+            with_retry(retries, || fs::remove_file(&path)).unwrap();
+        });
+    }
+
+    let duration = stopwatch.elapsed();
+
+    // Clean up! Otherwise Veracruz may try to copy it back over
+    // into the user's fs, which is a waste of (significant) time...
+    //
+    for i in count..count+population {
+        fs::remove_file(format!("{}/{:09x}.txt", path, i)).unwrap();
+    }
+    fs::remove_dir(&path).unwrap();
+
+    Report::new(duration)
+        .with("population", population)
+        .with("steps", count)
+        .with("steps_per_sec", count as f64 / duration.as_secs_f64())
+}
+
+/// Populate a tree of size/block_size files, then time copying it
+/// recursively
+///
+/// std has no copy_dir, so this does what build tooling usually does,
+/// walking the tree with read_dir and copying each file with fs::copy,
+/// which exercises creates, writes and metadata together.
+pub fn copy_tree(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, .. } = *config;
+    let path = format!("{}/copy_tree_{}_{}_{}", config.scratch_dir, size, block_size, run);
+    let from = format!("{}/from", path);
+    let to = format!("{}/to", path);
+    scratch::create_dir(config, &path);
+
+    // first populate the source tree
+    fs::create_dir(&from).unwrap();
+    create_tree(config, &from);
+
+    // then measure copying it
+    let stopwatch = Instant::now();
+
+    let (files, bytes) = hint::black_box(
+        supported!(
+            copy_dir(retries, hint::black_box(&from), &to),
+            fs::remove_dir_all(&path).unwrap()
+        )
+    );
+
+    let duration = stopwatch.elapsed();
+
+    // Clean up! Otherwise Veracruz may try to copy it back over
+    // into the user's fs, which is a waste of (significant) time...
+    //
+    fs::remove_dir_all(&path).unwrap();
+
+    Report::new(duration)
+        .with("files_copied", files)
+        .with("bytes_copied", bytes)
+        .with("bytes_per_sec", bytes as f64 / duration.as_secs_f64())
+}
+
+/// Create a tree of size/block_size files under the existing path, with --files-per-dir
+/// files to a directory and --files-per-dir directories to each directory
+/// above them, so anything but tiny trees are nested two levels deep
+///
+/// This phase is never timed.
+fn create_tree(config: &Config, path: &str) {
+    let Config { size, block_size, retries, seed, files_per_dir, .. } = *config;
+    let mut prng = Prng::new(config.prng, seed);
+    let mut buffer = vec![0u8; block_size];
+
+    let count = size/u64::try_from(block_size).unwrap();
+    for i in 0..count {
+        let parent = format!("{}/{:09x}", path, i/files_per_dir/files_per_dir);
+        let dir = format!("{}/{:09x}", parent, i/files_per_dir);
+        if i % (files_per_dir*files_per_dir) == 0 {
+            fs::create_dir(&parent).unwrap();
+        }
+        if i % files_per_dir == 0 {
+            fs::create_dir(&dir).unwrap();
+        }
+
+        for (x, y) in buffer.iter_mut().zip(&mut prng) {
+            *x = y as u8;
+        }
+        mark(config, &mut buffer);
+
+        let mut file = with_retry(retries, || File::create(format!("{}/{:09x}.txt", dir, i))).unwrap();
+        with_retry(retries, || file.write_all(&buffer)).unwrap();
+        file.flush().unwrap();
+    }
+}
+
+/// Recursively copy a directory, returning how many files and bytes were
+/// copied
+fn copy_dir(retries: u32, from: &str, to: &str) -> io::Result<(u64, u64)> {
+    with_retry(retries, || fs::create_dir(to))?;
+
+    let mut files = 0;
+    let mut bytes = 0;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let name = name.to_str().unwrap();
+        let from = format!("{}/{}", from, name);
+        let to = format!("{}/{}", to, name);
+
+        if entry.file_type()?.is_dir() {
+            let (dir_files, dir_bytes) = copy_dir(retries, &from, &to)?;
+            files += dir_files;
+            bytes += dir_bytes;
+        } else {
+            bytes += with_retry(retries, || fs::copy(&from, &to))?;
+            files += 1;
+        }
+    }
+
+    Ok((files, bytes))
+}
+
+/// Populate a tree of size/block_size files, then time walking it and
+/// summing the length of every file, as du does
+///
+/// Each file is stat'ed by path, so this combines directory enumeration
+/// with per-file metadata lookups.
+pub fn tree_size(config: &Config) -> Report {
+    let Config { size, block_size, run, .. } = *config;
+    let path = format!("{}/tree_size_{}_{}_{}", config.scratch_dir, size, block_size, run);
+
+    // first populate the tree
+    scratch::create_dir(config, &path);
+    create_tree(config, &path);
+
+    // then measure walking it
+    let stopwatch = Instant::now();
+
+    let (files, bytes) = hint::black_box(
+        dir_size(hint::black_box(&path)).unwrap()
+    );
+
+    let duration = stopwatch.elapsed();
+
+    // Clean up! Otherwise Veracruz may try to copy it back over
+    // into the user's fs, which is a waste of (significant) time...
+    //
+    fs::remove_dir_all(&path).unwrap();
+
+    Report::new(duration)
+        .with("files", files)
+        .with("bytes", bytes)
+        .with("files_per_sec", files as f64 / duration.as_secs_f64())
+}
+
+/// List a directory of size/block_size files with read_dir while another
+/// thread keeps creating and removing other files in it
+///
+/// Whether a listing running alongside changes may skip or repeat entries
+/// is up to the VFS. The pre-created files are never touched, so each of
+/// them missing from a pass is counted as skipped, and any entry seen twice
+/// in a pass as duplicated, along with any errors from the iterator. Only
+/// the listing is measured.
+pub fn list_churn(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, .. } = *config;
+    let path = format!("{}/list_churn_{}_{}_{}", config.scratch_dir, size, block_size, run);
+    scratch::create_dir(config, &path);
+    let count = size/u64::try_from(block_size).unwrap();
+
+    // first create the files that stay put
+    create_files(config, &path);
+    let stable = (0..count)
+        .map(|i| OsString::from(format!("{:09x}.txt", i)))
+        .collect::<HashSet<_>>();
+
+    let done = AtomicBool::new(false);
+    let mut listed = 0u64;
+    let mut errors = 0u64;
+    let mut duplicates = 0u64;
+    let mut skipped = 0u64;
+
+    thread::scope(|scope| {
+        let churn = thread::Builder::new().spawn_scoped(scope, || {
+            let mut churned = 0u64;
+            while !done.load(Ordering::Acquire) {
+                let path = format!("{}/churn_{:09x}.txt", path, churned % count.max(1));
+                with_retry(retries, || File::create(&path)).unwrap();
+                fs::remove_file(&path).unwrap();
+                churned += 1;
+            }
+            churned
+        });
+        // the scope waits for the churn thread, so it must be stopped on every
+        // way out of here, not least supported! returning early
+        let stop = SetOnDrop(&done);
+        // wasm32-wasi has no threads
+        let churn = supported!(churn, fs::remove_dir_all(&path).unwrap());
+
+        let mut duration = Duration::ZERO;
+        for _ in 0..LIST_PASSES {
+            let mut seen = HashSet::new();
+            let stopwatch = Instant::now();
+
+            let entries = supported!(fs::read_dir(hint::black_box(&path)), {
+                mem::drop(stop);
+                churn.join().unwrap();
+                fs::remove_dir_all(&path).unwrap();
+            });
+            for entry in entries {
+                match hint::black_box(entry) {
+                    Ok(entry) => {
+                        if !seen.insert(entry.file_name()) {
+                            duplicates += 1;
+                        }
+                        listed += 1;
+                    }
+                    Err(_) => errors += 1,
+                }
+            }
+
+            duration += stopwatch.elapsed();
+            skipped += u64::try_from(
+                stable.iter().filter(|&name| !seen.contains(name)).count()
+            ).unwrap();
+        }
+
+        mem::drop(stop);
+        let churned = churn.join().unwrap();
+
+        // Clean up! Otherwise Veracruz may try to copy it back over
+        // into the user's fs, which is a waste of (significant) time...
+        //
+        for i in 0..count {
+            let path = format!("{}/{:09x}.txt", path, i);
+            let file = with_retry(retries, || File::create(&path)).unwrap();
+            file.set_len(0).unwrap();
+        }
+
+        Report::new(duration)
+            .with("passes", LIST_PASSES)
+            .with("entries_listed", listed)
+            .with("entries_per_sec", listed as f64 / duration.as_secs_f64())
+            .with("churned_files", churned)
+            .with("iteration_errors", errors)
+            .with("duplicated_entries", duplicates)
+            .with("skipped_entries", skipped)
+    })
+}
+
+/// Recursively sum the lengths of the files in a directory, returning how
+/// many files there were and their total length
+fn dir_size(path: &str) -> io::Result<(u64, u64)> {
+    let mut files = 0;
+    let mut bytes = 0;
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let path = format!("{}/{}", path, entry.file_name().to_str().unwrap());
+
+        if entry.file_type()?.is_dir() {
+            let (dir_files, dir_bytes) = dir_size(&path)?;
+            files += dir_files;
+            bytes += dir_bytes;
+        } else {
+            bytes += fs::metadata(&path)?.len();
+            files += 1;
+        }
+    }
+
+    Ok((files, bytes))
+}
+
+/// Create size/block_size tiny files in one directory, with create latency
+/// averaged over every CREATE_BUCKET files to show how it degrades as the
+/// directory grows
+///
+/// Meant to be run with a tiny block_size and a large size, to find where
+/// the VFS's inode or directory-entry capacity starts to hurt. Running out
+/// of space or inodes stops the run rather than aborting it, and is
+/// reported along with how many files we managed to create.
+pub fn create_many(config: &Config) -> Report {
+    let Config { size, block_size, run, retries, seed, .. } = *config;
+    let path = format!("{}/small_create_many_{}_{}_{}", config.scratch_dir, size, block_size, run);
+    let mut prng = Prng::new(config.prng, seed);
+    let mut buffer = vec![0u8; block_size];
+    scratch::create_dir(config, &path);
+
+    let count = size/u64::try_from(block_size).unwrap();
+    let mut buckets = Vec::new();
+    let mut bucket = Duration::ZERO;
+    let mut created = 0u64;
+    let mut create_error = None;
+    let stopwatch = Instant::now();
+
+    for i in 0..count {
+        if interrupted() {
+            break;
+        }
+
+        let path = format!("{}/{:09x}.txt", path, i);
+
+        for (x, y) in buffer.iter_mut().zip(&mut prng) {
+            *x = y as u8;
+        }
+        mark(config, &mut buffer);
+
+        let create_stopwatch = Instant::now();
+        let res = hint::black_box({
+            let path = hint::black_box(&path);
+            with_retry(retries, || File::create(path)).and_then(|mut file| {
+                let input = hint::black_box(&buffer);
+                with_retry(retries, || file.write_all(input))?;
+                file.flush()
+            })
+        });
+        bucket += create_stopwatch.elapsed();
+
+        if let Err(err) = res {
+            create_error = Some(err);
+            break;
+        }
+        created += 1;
+
+        if created.is_multiple_of(CREATE_BUCKET) {
+            buckets.push(bucket.as_secs_f64() / CREATE_BUCKET as f64);
+            bucket = Duration::ZERO;
+        }
+    }
+
+    let duration = stopwatch.elapsed();
+
+    // a trailing partial bucket is still worth seeing
+    if !created.is_multiple_of(CREATE_BUCKET) {
+        buckets.push(bucket.as_secs_f64() / (created % CREATE_BUCKET) as f64);
+    }
+
+    // Clean up! Otherwise Veracruz may try to copy it back over into the
+    // user's fs, which is a waste of (significant) time, this includes any
+    // file left behind by a failed create
+    //
+    for i in 0..min(created+1, count) {
+        match fs::remove_file(format!("{}/{:09x}.txt", path, i)) {
+            Err(err) if err.kind() == ErrorKind::NotFound => {}
+            res => res.unwrap(),
+        }
+    }
+    fs::remove_dir(&path).unwrap();
+
+    let report = Report::new(duration)
+        .with_progress(config, created)
+        .with("files_created", created)
+        .with("bucket_files", CREATE_BUCKET)
+        .with("bucket_create_latency", buckets);
+
+    match create_error {
+        Some(err) => report
+            .with("out_of_space", out_of_space(&err))
+            .with("create_error", err.to_string()),
+        None => report,
+    }
+}
+
+/// Whether an error means the filesystem ran out of space or inodes
+fn out_of_space(err: &io::Error) -> bool {
+    matches!(err.kind(), ErrorKind::StorageFull | ErrorKind::QuotaExceeded)
 }
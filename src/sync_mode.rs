@@ -0,0 +1,60 @@
+//! Syncing written files to stable storage
+//!
+//! ## Authors
+//!
+//! The Veracruz Development Team.
+//!
+//! ## Copyright
+//!
+//! See the file `LICENSING.markdown` in the Veracruz root directory for licensing
+//! and copyright information.
+
+use crate::retry::with_retry;
+use anyhow::bail;
+use serde::Serialize;
+use std::{
+    fs::File,
+    io,
+    str::FromStr,
+};
+
+/// What the write modes do after their last write, still inside the timed
+/// region
+///
+/// Where the platform distinguishes them, syncing only the data
+/// (fdatasync) can skip flushing metadata such as the mtime that a full
+/// sync (fsync) also writes, this lets the two costs be compared.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncMode {
+    /// leave the data wherever the VFS has it
+    None,
+    /// File::sync_data
+    Data,
+    /// File::sync_all
+    Full,
+}
+
+impl SyncMode {
+    /// Sync the file as this mode asks, retrying if interrupted
+    pub fn sync(self, file: &File, attempts: u32) -> io::Result<()> {
+        match self {
+            SyncMode::None => Ok(()),
+            SyncMode::Data => with_retry(attempts, || file.sync_data()),
+            SyncMode::Full => with_retry(attempts, || file.sync_all()),
+        }
+    }
+}
+
+impl FromStr for SyncMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "none" => Ok(SyncMode::None),
+            "data" => Ok(SyncMode::Data),
+            "full" => Ok(SyncMode::Full),
+            _ => bail!("Unknown sync mode {:?}, expected none, data or full", s),
+        }
+    }
+}
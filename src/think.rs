@@ -0,0 +1,33 @@
+//! Pausing between operations to model bursty workloads
+//!
+//! ## Authors
+//!
+//! The Veracruz Development Team.
+//!
+//! ## Copyright
+//!
+//! See the file `LICENSING.markdown` in the Veracruz root directory for licensing
+//! and copyright information.
+
+use crate::Config;
+use std::{
+    thread,
+    time::Duration,
+    time::Instant,
+};
+
+/// Pause for --think-us microseconds, returning how long was actually
+/// spent so the caller can take it out of its measurement
+///
+/// Sleeps routinely overshoot, so it's the measured pause, not the
+/// requested one, that must be subtracted. With the default of zero this
+/// returns immediately.
+pub fn think(config: &Config) -> Duration {
+    if config.think_us == 0 {
+        return Duration::ZERO;
+    }
+
+    let stopwatch = Instant::now();
+    thread::sleep(Duration::from_micros(config.think_us));
+    stopwatch.elapsed()
+}
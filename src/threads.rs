@@ -0,0 +1,59 @@
+//! Running benchmark work across threads
+//!
+//! ## Authors
+//!
+//! The Veracruz Development Team.
+//!
+//! ## Copyright
+//!
+//! See the file `LICENSING.markdown` in the Veracruz root directory for licensing
+//! and copyright information.
+
+use std::{
+    convert::TryFrom,
+    sync::Barrier,
+    sync::atomic::AtomicBool,
+    sync::atomic::Ordering,
+    thread,
+    time::Duration,
+    time::Instant,
+};
+
+/// Run f(0), f(1), ... f(threads-1) in parallel, returning the time from
+/// when all threads are released until the last one finishes
+///
+/// Thread spawning is excluded by holding every thread at a barrier until
+/// all have started. wasm32-wasi has no threads, so with a single thread f
+/// runs on the calling thread instead.
+pub fn timed_parallel(threads: u32, f: impl Fn(u32) + Sync) -> Duration {
+    if threads == 1 {
+        let stopwatch = Instant::now();
+        f(0);
+        return stopwatch.elapsed();
+    }
+
+    let barrier = Barrier::new(usize::try_from(threads).unwrap() + 1);
+    thread::scope(|scope| {
+        for t in 0..threads {
+            let barrier = &barrier;
+            let f = &f;
+            scope.spawn(move || {
+                barrier.wait();
+                f(t);
+            });
+        }
+
+        barrier.wait();
+        Instant::now()
+    }).elapsed()
+}
+
+/// Sets a flag when dropped, so a thread polling it is stopped however its
+/// scope is left, including an early return or a panic
+pub struct SetOnDrop<'a>(pub &'a AtomicBool);
+
+impl Drop for SetOnDrop<'_> {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Release);
+    }
+}
@@ -0,0 +1,64 @@
+//! Units of the reported runtime
+//!
+//! ## Authors
+//!
+//! The Veracruz Development Team.
+//!
+//! ## Copyright
+//!
+//! See the file `LICENSING.markdown` in the Veracruz root directory for licensing
+//! and copyright information.
+
+use anyhow::bail;
+use serde::Serialize;
+use std::{
+    str::FromStr,
+    time::Duration,
+};
+
+/// Unit of the runtime field of the results
+///
+/// Seconds are awkward for sub-millisecond metadata operations, so this
+/// lets the runtime be reported in whatever unit suits the mode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeUnit {
+    S,
+    Ms,
+    Us,
+    Ns,
+}
+
+impl TimeUnit {
+    /// Number of this unit in a second
+    pub fn per_second(self) -> f64 {
+        match self {
+            TimeUnit::S => 1e0,
+            TimeUnit::Ms => 1e3,
+            TimeUnit::Us => 1e6,
+            TimeUnit::Ns => 1e9,
+        }
+    }
+
+    /// Express a duration in this unit
+    ///
+    /// Dividing whole nanoseconds by an exact power of ten keeps the float
+    /// from carrying noise beyond the nanoseconds actually measured.
+    pub fn scale(self, duration: Duration) -> f64 {
+        duration.as_nanos() as f64 / (1e9 / self.per_second())
+    }
+}
+
+impl FromStr for TimeUnit {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "s" => Ok(TimeUnit::S),
+            "ms" => Ok(TimeUnit::Ms),
+            "us" => Ok(TimeUnit::Us),
+            "ns" => Ok(TimeUnit::Ns),
+            _ => bail!("Unknown time unit {:?}, expected s, ms, us or ns", s),
+        }
+    }
+}